@@ -1,103 +1,404 @@
+use crate::dary_heap::DaryHeap;
 use std::cmp::Ordering;
-use std::thread::panicking;
+// 位置从 1 开始编号。二叉堆中位置 index 的子节点为 2·index、2·index + 1，父节点为 index / 2；
+// 带上叉数 d 时，子节点为 left_child!(index, d) ..= right_child!(index, d)。
 macro_rules! left_child {
     ($index: ident) => {
         $index << 1
     };
+    ($index: ident, $arity: expr) => {
+        $arity * ($index - 1) + 2
+    };
 }
 
 macro_rules! right_child {
     ($index: ident) => {
         ($index << 1) + 1
     };
+    ($index: ident, $arity: expr) => {
+        $arity * $index + 1
+    };
 }
 
 macro_rules! parent {
     ($index: ident) => {
         $index >> 1
     };
+    ($index: ident, $arity: expr) => {
+        ($index - 2) / $arity + 1
+    };
 }
-struct BinaryHeap<T: Ord + Default> {
-    size: usize,
-    data: Vec<T>,
+
+pub(crate) use parent;
+
+/// Decides which of two elements leaves the heap first: `Less` means `a` pops before `b`.
+pub trait Compare<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
 }
 
-impl<T: Ord + Default> BinaryHeap<T> {
-    pub fn new() -> Self {
-        BinaryHeap {
-            size: 0,
-            data: vec![T::default()],
-        }
+/// Smallest element first, by `Ord`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinOrder;
+
+/// Largest element first, by `Ord`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaxOrder;
+
+/// Order given by a comparator closure, see `BinaryHeap::by`.
+#[derive(Clone, Copy, Debug)]
+pub struct FnOrder<F>(pub F);
+
+/// Smallest key first, see `BinaryHeap::by_key`.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyOrder<F>(pub F);
+
+impl<T: Ord + ?Sized> Compare<T> for MinOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
     }
+}
 
-    pub fn is_empty(&self) -> bool {
-        self.size == 0
+impl<T: Ord + ?Sized> Compare<T> for MaxOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        b.cmp(a)
     }
+}
 
-    fn move_up(&mut self, mut index: usize) {
-        loop {
-            let parent_index = parent!(index);
-            if parent_index == 0 {
-                break;
-            }
-            if let Ordering::Less = self.data[index].cmp(&self.data[parent_index]) {
-                self.data.swap(parent_index, index);
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Compare<T> for FnOrder<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+impl<T: ?Sized, K: Ord, F: Fn(&T) -> K> Compare<T> for KeyOrder<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+impl<T: ?Sized, C: Compare<T> + ?Sized> Compare<T> for &C {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (**self).compare(a, b)
+    }
+}
+
+// 二叉堆就是叉数为 2 的 d 叉堆，两者共用 dary_heap 中的同一份实现。
+// 位置 i 的元素存放在 data[i - 1]，堆顶是按 C 排在最前面的元素，默认 MinOrder 即小顶堆。
+#[allow(unused)]
+pub type BinaryHeap<T, C = MinOrder> = DaryHeap<T, 2, C>;
+
+#[allow(unused)]
+pub use crate::dary_heap::{DrainSorted, PeekMut};
+
+// 以下的 sift 函数都按 D 叉堆处理 data，位置从 1 开始编号，堆的大小就是 data.len()。
+
+// 把位置 index 的元素往上浮，直到它不比父节点更靠前
+pub(crate) fn sift_up<const D: usize, T, C: Compare<T>>(data: &mut [T], cmp: &C, index: usize) {
+    sift_up_tracked::<D, _, _, _>(data, cmp, index, |_, _| {});
+}
+
+// 同 sift_up，每次交换后对换到新位置的两个元素调用 moved(元素, 新位置)，
+// 索引优先队列用它同步 id 到位置的映射
+pub(crate) fn sift_up_tracked<const D: usize, T, C: Compare<T>, F: FnMut(&T, usize)>(
+    data: &mut [T],
+    cmp: &C,
+    mut index: usize,
+    mut moved: F,
+) {
+    while index > 1 {
+        let parent_index = parent!(index, D);
+        if let Ordering::Less = cmp.compare(&data[index - 1], &data[parent_index - 1]) {
+            data.swap(parent_index - 1, index - 1);
+            moved(&data[parent_index - 1], parent_index);
+            moved(&data[index - 1], index);
+        } else {
+            break;
+        }
+        index = parent_index;
+    }
+}
+
+// 把位置 index 的元素往下沉，每层在 D 个子节点中选出最靠前的一个比较
+pub(crate) fn sift_down<const D: usize, T, C: Compare<T>>(data: &mut [T], cmp: &C, index: usize) {
+    sift_down_tracked::<D, _, _, _>(data, cmp, index, |_, _| {});
+}
+
+// 同 sift_down，回调的含义与 sift_up_tracked 相同
+pub(crate) fn sift_down_tracked<const D: usize, T, C: Compare<T>, F: FnMut(&T, usize)>(
+    data: &mut [T],
+    cmp: &C,
+    mut index: usize,
+    mut moved: F,
+) {
+    let size = data.len();
+    loop {
+        let first_child = left_child!(index, D);
+        if first_child > size {
+            break;
+        }
+        let last_child = right_child!(index, D).min(size);
+        let mut min_index = first_child;
+        for child in first_child + 1..=last_child {
+            if let Ordering::Less = cmp.compare(&data[child - 1], &data[min_index - 1]) {
+                min_index = child;
             }
-            index = parent_index;
         }
+        if let Ordering::Greater = cmp.compare(&data[index - 1], &data[min_index - 1]) {
+            data.swap(index - 1, min_index - 1);
+            moved(&data[index - 1], index);
+            moved(&data[min_index - 1], min_index);
+        } else {
+            break;
+        }
+        index = min_index;
     }
+}
 
-    pub fn push(&mut self, val: T) {
-        self.size += 1;
-        self.data.push(val);
-        self.move_up(self.size);
+// 自底向上建堆：从最后一个非叶子节点（最后一个元素的父节点）开始依次下沉。以二叉堆为例，
+// 高度为 h 的节点最多下沉 h 层，而高度为 h 的节点数不超过 n / 2^(h+1)，总代价
+// Σ h·n / 2^(h+1) = O(n)；D 叉堆同理。
+pub(crate) fn heapify<const D: usize, T, C: Compare<T>>(data: &mut [T], cmp: &C) {
+    let len = data.len();
+    if len < 2 {
+        return;
     }
+    for index in (1..=parent!(len, D)).rev() {
+        sift_down::<D, _, _>(data, cmp, index);
+    }
+}
 
-    fn move_down(&mut self, mut index: usize) {
-        loop {
-            let left_index = left_child!(index);
-            if left_index > self.size {
-                break;
+// 原地堆排序：建堆后不断把堆顶换到末尾并缩小堆，最先出堆的元素落在最后。
+fn sort_by_heap<T, C: Compare<T>>(data: &mut [T], cmp: &C) {
+    heapify::<2, _, _>(data, cmp);
+    for end in (1..data.len()).rev() {
+        data.swap(0, end);
+        sift_down::<2, _, _>(&mut data[..end], cmp, 1);
+    }
+}
+
+/// Sorts the slice in ascending order in place, in O(n log n) time and O(1) extra space.
+/// The sort is not stable.
+#[allow(unused)]
+pub fn heap_sort<T: Ord>(data: &mut [T]) {
+    sort_by_heap(data, &MaxOrder);
+}
+
+/// Sorts the slice in place so that `compare` is ascending, like `slice::sort_unstable_by`.
+#[allow(unused)]
+pub fn heap_sort_by<T, F: Fn(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
+    // 堆顶最后落在末尾，所以用反过来的比较器建堆
+    sort_by_heap(data, &FnOrder(|a: &T, b: &T| compare(b, a)));
+}
+
+// 各种堆共用的测试：heap_test_suite!(Heap) 生成基本操作的测试，
+// heap_test_suite!(Heap, Handle) 再加上 meld 和 decrease_key 的测试。
+// 调用处需要先把 Heap（和 Handle）引入当前模块。
+#[cfg(test)]
+macro_rules! heap_test_suite {
+    ($heap: ident) => {
+        mod heap_suite {
+            use super::$heap;
+            use crate::binary_heap::{Compare, MaxOrder};
+            use rand::{thread_rng, Rng};
+            use std::cmp::Reverse;
+            use std::collections::BinaryHeap as StdBinaryHeap;
+
+            fn pop_all<T, C: Compare<T>>(mut heap: $heap<T, C>) -> Vec<T> {
+                let mut popped = Vec::new();
+                while let Some(val) = heap.pop() {
+                    popped.push(val);
+                }
+                popped
             }
-            let right_index = right_child!(index);
-            let min_index = match (left_index, right_index) {
-                (left_index, right_index) if right_index > self.size => left_index,
-                (left_index, right_index) => {
-                    if let Ordering::Less = self.data[left_index].cmp(&self.data[right_index]) {
-                        left_index
+
+            #[test]
+            fn test_pop_order() {
+                let mut heap = $heap::new();
+                for val in [2, 8, 1, 9, 5, 4, 6, 3, 5] {
+                    heap.push(val);
+                }
+                assert_eq!(heap.len(), 9);
+                assert_eq!(heap.peek().map(|val| *val), Some(1));
+                assert_eq!(pop_all(heap), [1, 2, 3, 4, 5, 5, 6, 8, 9]);
+            }
+
+            // 没有 Default 的类型也能放进堆里
+            #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+            struct Task {
+                priority: u32,
+                name: &'static str,
+            }
+
+            #[test]
+            fn test_without_default() {
+                let mut heap = $heap::new();
+                heap.push(Task {
+                    priority: 2,
+                    name: "write",
+                });
+                heap.push(Task {
+                    priority: 1,
+                    name: "read",
+                });
+                assert_eq!(heap.pop().map(|task| task.name), Some("read"));
+                assert_eq!(heap.pop().map(|task| task.name), Some("write"));
+                assert!(heap.pop().is_none());
+                assert!(heap.is_empty());
+            }
+
+            #[test]
+            fn test_orders() {
+                let mut heap = $heap::new_max();
+                for val in [2, 8, 1, 9, 5, 4, 6, 3] {
+                    heap.push(val);
+                }
+                assert_eq!(pop_all(heap), [9, 8, 6, 5, 4, 3, 2, 1]);
+
+                let mut heap = $heap::with_comparator(MaxOrder);
+                heap.push("b");
+                heap.push("c");
+                heap.push("a");
+                assert_eq!(pop_all(heap), ["c", "b", "a"]);
+
+                let words = ["pear", "fig", "banana", "kiwi", "apple"];
+                let mut by_len =
+                    $heap::by(|a: &&str, b: &&str| b.len().cmp(&a.len()).then(a.cmp(b)));
+                for word in words {
+                    by_len.push(word);
+                }
+                assert_eq!(pop_all(by_len), ["banana", "apple", "kiwi", "pear", "fig"]);
+
+                // 比较器可以借用外部数据，比如按节点在距离表中的值排序
+                let dist = [7, 3, 9, 1];
+                let mut by_key = $heap::by_key(|node: &usize| dist[*node]);
+                for node in 0..dist.len() {
+                    by_key.push(node);
+                }
+                assert_eq!(pop_all(by_key), [3, 1, 0, 2]);
+            }
+
+            #[test]
+            fn test_matches_std() {
+                let mut rng = thread_rng();
+                let mut heap = $heap::new();
+                let mut std_heap = StdBinaryHeap::new();
+                for _ in 0..20_000 {
+                    if rng.gen_bool(0.6) {
+                        let val = rng.gen_range(0..1_000);
+                        heap.push(val);
+                        std_heap.push(Reverse(val));
                     } else {
-                        right_index
+                        assert_eq!(heap.pop(), std_heap.pop().map(|val| val.0));
                     }
+                    assert_eq!(heap.len(), std_heap.len());
+                    assert_eq!(
+                        heap.peek().map(|val| *val),
+                        std_heap.peek().map(|val| val.0)
+                    );
                 }
-            };
-            if let Ordering::Greater = self.data[index].cmp(&self.data[min_index]) {
-                self.data.swap(index, min_index);
+                heap.clear();
+                assert!(heap.is_empty());
+                assert!(heap.peek().is_none());
             }
-            index = min_index;
         }
-    }
+    };
+    ($heap: ident, $handle: ident) => {
+        heap_test_suite!($heap);
 
-    pub fn pop(&mut self) -> Option<T> {
-        match self.size {
-            0 => None,
-            1 => {
-                self.size -= 1;
-                self.data.pop()
+        mod meldable_heap_suite {
+            use super::{$handle, $heap};
+            use crate::binary_heap::Compare;
+            use rand::{thread_rng, Rng};
+
+            fn pop_all<T, C: Compare<T>>(mut heap: $heap<T, C>) -> Vec<T> {
+                let mut popped = Vec::new();
+                while let Some(val) = heap.pop() {
+                    popped.push(val);
+                }
+                popped
             }
-            size => {
-                self.size -= 1;
-                self.data.swap(1, size);
-                let data = self.data.pop();
-                self.move_down(1);
-                data
+
+            #[test]
+            fn test_meld() {
+                let mut rng = thread_rng();
+                for (left, right) in [(0, 100), (100, 0), (1_000, 3), (3, 1_000), (500, 500)] {
+                    let left: Vec<u32> = (0..left).map(|_| rng.gen_range(0..100)).collect();
+                    let right: Vec<u32> = (0..right).map(|_| rng.gen_range(0..100)).collect();
+                    let mut heap = $heap::new();
+                    let mut other = $heap::new();
+                    left.iter().for_each(|val| drop(heap.push(*val)));
+                    let handles: Vec<_> = right.iter().map(|val| other.push(*val)).collect();
+                    heap.meld(other);
+                    assert_eq!(heap.len(), left.len() + right.len());
+                    // 合并后原来的句柄仍然可用
+                    for handle in &handles {
+                        assert!(heap.decrease_key(handle, 0));
+                    }
+                    let mut expected = [left, vec![0; right.len()]].concat();
+                    expected.sort();
+                    assert_eq!(pop_all(heap), expected);
+                }
+            }
+
+            // 随机 decrease_key，与记录当前值的数组对比
+            #[test]
+            fn test_decrease_key() {
+                let mut rng = thread_rng();
+                let mut heap = $heap::new();
+                let mut handles: Vec<($handle<(u32, usize)>, u32)> = Vec::new();
+                for id in 0..2_000 {
+                    let val = rng.gen_range(1_000..100_000);
+                    handles.push((heap.push((val, id)), val));
+                }
+                for _ in 0..5_000 {
+                    let id = rng.gen_range(0..handles.len());
+                    let (handle, val) = &mut handles[id];
+                    let new_val = rng.gen_range(0..=*val);
+                    assert!(heap.decrease_key(handle, (new_val, id)));
+                    *val = new_val;
+                    // 往后调整的会被拒绝
+                    assert!(!heap.decrease_key(handle, (*val + 1, id)));
+                }
+                let mut expected: Vec<_> = handles
+                    .iter()
+                    .enumerate()
+                    .map(|(id, (_, val))| (*val, id))
+                    .collect();
+                expected.sort();
+                assert_eq!(heap.peek().map(|val| *val), expected.first().copied());
+                assert_eq!(pop_all(heap), expected);
+            }
+
+            #[test]
+            fn test_stale_handle() {
+                let mut heap = $heap::new();
+                let first = heap.push(1);
+                let second = heap.push(2);
+                assert_eq!(heap.pop(), Some(1));
+                assert!(!heap.decrease_key(&first, 0));
+                assert!(heap.decrease_key(&second, 0));
+                assert_eq!(heap.pop(), Some(0));
+                assert!(!heap.decrease_key(&second, 0));
+                assert!(heap.is_empty());
             }
         }
-    }
+    };
 }
 
+#[cfg(test)]
+pub(crate) use heap_test_suite;
+
 #[cfg(test)]
 mod tests {
-    use crate::binary_heap::BinaryHeap;
+    use crate::binary_heap::{
+        heap_sort, heap_sort_by, BinaryHeap, Compare, FnOrder, MaxOrder, PeekMut,
+    };
+    use rand::{thread_rng, Rng};
+    use std::cell::Cell;
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap as StdBinaryHeap;
+
+    heap_test_suite!(BinaryHeap);
 
     #[test]
     fn test_marco() {
@@ -121,4 +422,243 @@ mod tests {
             println!("{:?}", heap.pop());
         }
     }
+
+    #[test]
+    fn test_len_and_capacity() {
+        let mut heap = BinaryHeap::with_capacity(10);
+        assert!(heap.capacity() >= 10);
+        assert_eq!(heap.len(), 0);
+        assert!(heap.is_empty());
+        for val in 0..10 {
+            heap.push(val);
+        }
+        assert_eq!(heap.len(), 10);
+        heap.pop();
+        assert_eq!(heap.len(), 9);
+        assert_eq!(BinaryHeap::<u8>::new().capacity(), 0);
+    }
+
+    fn pop_all<T, C: Compare<T>>(mut heap: BinaryHeap<T, C>) -> Vec<T> {
+        heap.drain_sorted().collect()
+    }
+
+    fn random_vec(len: usize, max: u32) -> Vec<u32> {
+        let mut rng = thread_rng();
+        (0..len).map(|_| rng.gen_range(0..max)).collect()
+    }
+
+    #[test]
+    fn test_from_vec_matches_std() {
+        for len in [0, 1, 2, 3, 10, 100, 1_000] {
+            let data = random_vec(len, 50);
+            let mut heap = BinaryHeap::from(data.clone());
+            let mut std_heap: StdBinaryHeap<_> = data.iter().copied().map(Reverse).collect();
+            assert_eq!(heap.len(), std_heap.len());
+            loop {
+                assert_eq!(heap.peek().copied(), std_heap.peek().map(|val| val.0));
+                let val = heap.pop();
+                assert_eq!(val, std_heap.pop().map(|val| val.0));
+                if val.is_none() {
+                    break;
+                }
+            }
+
+            let heap: BinaryHeap<_> = data.iter().copied().collect();
+            let mut sorted = data.clone();
+            sorted.sort();
+            assert_eq!(heap.into_sorted_vec(), sorted);
+        }
+    }
+
+    // 自底向上建堆的比较次数不超过 2n
+    #[test]
+    fn test_heapify_is_linear() {
+        let len = 100_000;
+        let comparisons = Cell::new(0usize);
+        let cmp = FnOrder(|a: &u32, b: &u32| {
+            comparisons.set(comparisons.get() + 1);
+            a.cmp(b)
+        });
+        let heap = BinaryHeap::from_vec_with_comparator(random_vec(len, u32::MAX), &cmp);
+        assert_eq!(heap.len(), len);
+        assert!(
+            comparisons.get() <= 2 * len,
+            "{} comparisons",
+            comparisons.get()
+        );
+    }
+
+    #[test]
+    fn test_peek_mut_matches_std() {
+        let mut rng = thread_rng();
+        let data = random_vec(500, 1_000);
+        let mut heap = BinaryHeap::from(data.clone());
+        let mut std_heap: StdBinaryHeap<_> = data.into_iter().map(Reverse).collect();
+        for _ in 0..2_000 {
+            let delta = rng.gen_range(0..100);
+            if let Some(mut top) = heap.peek_mut() {
+                *top += delta;
+            }
+            if let Some(mut top) = std_heap.peek_mut() {
+                top.0 += delta;
+            }
+            assert_eq!(heap.peek().copied(), std_heap.peek().map(|val| val.0));
+        }
+        assert_eq!(
+            heap.into_sorted_vec(),
+            std_heap
+                .into_sorted_vec()
+                .into_iter()
+                .rev()
+                .map(|val| val.0)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_peek_mut_pop() {
+        let mut heap = BinaryHeap::new_max();
+        assert!(heap.peek_mut().is_none());
+        heap.push(3);
+        heap.push(7);
+        heap.push(5);
+        let top = heap.peek_mut().unwrap();
+        assert_eq!(PeekMut::pop(top), 7);
+        assert_eq!(heap.len(), 2);
+        assert_eq!(heap.peek(), Some(&5));
+    }
+
+    #[test]
+    fn test_into_sorted_vec_follows_order() {
+        let heap = BinaryHeap::from_vec_with_comparator(vec![2, 8, 1, 9, 5], MaxOrder);
+        assert_eq!(heap.into_sorted_vec(), [9, 8, 5, 2, 1]);
+        assert!(BinaryHeap::<u8>::new().into_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn test_heap_sort_matches_slice_sort() {
+        for len in [0, 1, 2, 3, 10, 100, 1_000, 10_000] {
+            let mut data = random_vec(len, len as u32 / 2 + 1);
+            let mut expected = data.clone();
+            expected.sort();
+            heap_sort(&mut data);
+            assert_eq!(data, expected);
+
+            let mut words: Vec<String> = random_vec(len, 1_000)
+                .iter()
+                .map(|val| val.to_string())
+                .collect();
+            let mut expected = words.clone();
+            expected.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+            heap_sort_by(&mut words, |a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+            assert_eq!(words, expected);
+        }
+    }
+
+    fn sorted(mut data: Vec<u32>) -> Vec<u32> {
+        data.sort();
+        data
+    }
+
+    #[test]
+    fn test_iter_and_into_iter() {
+        let data = random_vec(100, 20);
+        let heap = BinaryHeap::from(data.clone());
+        assert_eq!(sorted(heap.iter().copied().collect()), sorted(data.clone()));
+        let mut total = 0;
+        for val in &heap {
+            total += val;
+        }
+        assert_eq!(total, data.iter().sum());
+        assert_eq!(sorted(heap.into_iter().collect()), sorted(data));
+    }
+
+    #[test]
+    fn test_drain() {
+        let data = random_vec(100, 20);
+        let mut heap = BinaryHeap::from(data.clone());
+        let capacity = heap.capacity();
+        assert_eq!(sorted(heap.drain().collect()), sorted(data.clone()));
+        assert!(heap.is_empty());
+        assert_eq!(heap.capacity(), capacity);
+
+        heap.extend(&data);
+        assert_eq!(heap.drain_sorted().len(), 100);
+        assert!(heap.is_empty());
+
+        heap.extend(&data);
+        assert_eq!(
+            heap.drain_sorted().collect::<Vec<_>>(),
+            sorted(data.clone())
+        );
+        assert!(heap.is_empty());
+
+        // 提前丢弃迭代器也会清空堆
+        heap.extend(&data);
+        let first: Vec<_> = heap.drain_sorted().take(10).collect();
+        assert_eq!(first, sorted(data)[..10]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let data = random_vec(1_000, 100);
+        let mut heap = BinaryHeap::from(data.clone());
+        heap.retain(|val| val % 3 == 0);
+        let expected: Vec<_> = data.into_iter().filter(|val| val % 3 == 0).collect();
+        assert_eq!(heap.len(), expected.len());
+        assert_eq!(pop_all(heap), sorted(expected));
+    }
+
+    #[test]
+    fn test_extend_matches_std() {
+        let mut rng = thread_rng();
+        let mut heap = BinaryHeap::new();
+        let mut std_heap = StdBinaryHeap::new();
+        // 追加的数量有大有小，两种重建方式都会走到
+        for _ in 0..50 {
+            let batch = random_vec(rng.gen_range(0..200), 1_000);
+            heap.extend(batch.iter().copied());
+            std_heap.extend(batch.into_iter().map(Reverse));
+            for _ in 0..rng.gen_range(0..50) {
+                assert_eq!(heap.pop(), std_heap.pop().map(|val| val.0));
+            }
+        }
+        assert_eq!(
+            pop_all(heap),
+            std_heap
+                .into_sorted_vec()
+                .into_iter()
+                .rev()
+                .map(|val| val.0)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_append() {
+        for (left, right) in [(0, 100), (100, 0), (1_000, 3), (3, 1_000), (500, 500)] {
+            let left = random_vec(left, 100);
+            let right = random_vec(right, 100);
+            let mut heap = BinaryHeap::from_vec_with_comparator(left.clone(), MaxOrder);
+            let mut other = BinaryHeap::from_vec_with_comparator(right.clone(), MaxOrder);
+            heap.append(&mut other);
+            assert!(other.is_empty());
+            let mut expected = sorted([left, right].concat());
+            expected.reverse();
+            assert_eq!(pop_all(heap), expected);
+        }
+    }
+
+    #[test]
+    fn test_clear_and_shrink_to_fit() {
+        let mut heap: BinaryHeap<_> = (0..1_000).collect();
+        heap.clear();
+        assert!(heap.is_empty());
+        assert!(heap.capacity() >= 1_000);
+        heap.shrink_to_fit();
+        assert_eq!(heap.capacity(), 0);
+        heap.push(1);
+        assert_eq!(heap.peek(), Some(&1));
+    }
 }
@@ -0,0 +1,294 @@
+// 二项堆：由若干棵阶数互不相同的二项树组成，k 阶二项树有 2^k 个节点，
+// 所以 n 个元素时各棵树的阶数正好对应 n 的二进制表示中为 1 的位。
+//
+// 两棵同阶的树比较一次根就能连成高一阶的树，meld 就像二进制加法一样逐位进位，O(log n)。
+// pop 删掉最靠前的根之后，它的孩子正好是 0..k 阶的一组二项树，再 meld 回去即可。
+// decrease_key 沿父节点往上交换，为了让句柄跟着元素走，节点里存的是 Entry 的引用，
+// 交换的是 Entry 而不是节点，同时更新 Entry 指回节点的指针。
+
+use crate::binary_heap::{Compare, FnOrder, KeyOrder, MaxOrder, MinOrder};
+use std::cell::{Ref, RefCell};
+use std::cmp::Ordering;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+struct Entry<T> {
+    value: T,
+    node: Weak<RefCell<Node<T>>>,
+}
+
+struct Node<T> {
+    entry: Rc<RefCell<Entry<T>>>,
+    parent: Weak<RefCell<Node<T>>>,
+    // children[i] 是 i 阶二项树
+    children: Vec<Tree<T>>,
+}
+
+type Tree<T> = Rc<RefCell<Node<T>>>;
+
+/// Refers to a value pushed into a `BinomialHeap`, for `decrease_key`.
+pub struct Handle<T>(Weak<RefCell<Entry<T>>>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(self.0.clone())
+    }
+}
+
+#[allow(unused)]
+pub struct BinomialHeap<T, C = MinOrder> {
+    // trees[k] 为 k 阶二项树
+    trees: Vec<Option<Tree<T>>>,
+    // 所有根中最靠前的元素，peek 为 O(1)
+    top: Option<Rc<RefCell<Entry<T>>>>,
+    len: usize,
+    cmp: C,
+}
+
+#[allow(unused)]
+impl<T: Ord> BinomialHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T: Ord> BinomialHeap<T, MaxOrder> {
+    pub fn new_max() -> Self {
+        Self::with_comparator(MaxOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T, F: Fn(&T, &T) -> Ordering> BinomialHeap<T, FnOrder<F>> {
+    /// Heap ordered by `cmp`, where `Less` means the first argument pops first.
+    pub fn by(cmp: F) -> Self {
+        Self::with_comparator(FnOrder(cmp))
+    }
+}
+
+#[allow(unused)]
+impl<T, K: Ord, F: Fn(&T) -> K> BinomialHeap<T, KeyOrder<F>> {
+    /// Heap that pops the element with the smallest `key` first.
+    pub fn by_key(key: F) -> Self {
+        Self::with_comparator(KeyOrder(key))
+    }
+}
+
+#[allow(unused)]
+impl<T, C: Compare<T>> BinomialHeap<T, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        BinomialHeap {
+            trees: Vec::new(),
+            top: None,
+            len: 0,
+            cmp,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn cmp_entry(&self, a: &Rc<RefCell<Entry<T>>>, b: &Rc<RefCell<Entry<T>>>) -> Ordering {
+        self.cmp.compare(&a.borrow().value, &b.borrow().value)
+    }
+
+    // 两棵 k 阶树连成一棵 k + 1 阶树，根靠后的一方成为另一方的最后一个孩子
+    fn link(&self, a: Tree<T>, b: Tree<T>) -> Tree<T> {
+        let ordering = self.cmp_entry(&a.borrow().entry, &b.borrow().entry);
+        let (winner, loser) = match ordering {
+            Ordering::Greater => (b, a),
+            _ => (a, b),
+        };
+        loser.borrow_mut().parent = Rc::downgrade(&winner);
+        winner.borrow_mut().children.push(loser);
+        winner
+    }
+
+    // 按二进制加法把 other 中的树并进来，other[k] 为 k 阶树
+    fn merge_trees(&mut self, other: Vec<Option<Tree<T>>>) {
+        let order = self.trees.len().max(other.len());
+        self.trees.resize(order, None);
+        let mut other = other.into_iter();
+        let mut carry = None;
+        for k in 0..order {
+            let sum = (self.trees[k].take(), other.next().flatten(), carry.take());
+            let (keep, next_carry) = match sum {
+                (None, None, None) => (None, None),
+                (Some(a), None, None) | (None, Some(a), None) | (None, None, Some(a)) => {
+                    (Some(a), None)
+                }
+                (Some(a), Some(b), None) | (Some(a), None, Some(b)) | (None, Some(a), Some(b)) => {
+                    (None, Some(self.link(a, b)))
+                }
+                (Some(a), Some(b), Some(c)) => (Some(c), Some(self.link(a, b))),
+            };
+            self.trees[k] = keep;
+            carry = next_carry;
+        }
+        if carry.is_some() {
+            self.trees.push(carry);
+        }
+        while self.trees.last().is_some_and(Option::is_none) {
+            self.trees.pop();
+        }
+        self.update_top();
+    }
+
+    fn update_top(&mut self) {
+        let mut top: Option<Rc<RefCell<Entry<T>>>> = None;
+        for tree in self.trees.iter().flatten() {
+            let entry = &tree.borrow().entry;
+            if top
+                .as_ref()
+                .is_none_or(|top| self.cmp_entry(entry, top) == Ordering::Less)
+            {
+                top = Some(entry.clone());
+            }
+        }
+        self.top = top;
+    }
+
+    /// Pushes `value` in O(log n) and returns a handle to it.
+    pub fn push(&mut self, value: T) -> Handle<T> {
+        let entry = Rc::new(RefCell::new(Entry {
+            value,
+            node: Weak::new(),
+        }));
+        let node = Rc::new(RefCell::new(Node {
+            entry: entry.clone(),
+            parent: Weak::new(),
+            children: Vec::new(),
+        }));
+        entry.borrow_mut().node = Rc::downgrade(&node);
+        self.len += 1;
+        self.merge_trees(vec![Some(node)]);
+        Handle(Rc::downgrade(&entry))
+    }
+
+    /// The element `pop` would return next.
+    pub fn peek(&self) -> Option<Ref<'_, T>> {
+        self.top
+            .as_ref()
+            .map(|entry| Ref::map(entry.borrow(), |entry| &entry.value))
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let top = self.top.take()?;
+        let node = top
+            .borrow()
+            .node
+            .upgrade()
+            .expect("queued entry has a node");
+        let order = node.borrow().children.len();
+        drop(node);
+        drop(top);
+        let tree = self.trees[order].take().expect("top entry sits at a root");
+        let Node {
+            entry, children, ..
+        } = Rc::into_inner(tree)
+            .expect("only the heap owns nodes")
+            .into_inner();
+        for child in &children {
+            child.borrow_mut().parent = Weak::new();
+        }
+        self.len -= 1;
+        self.merge_trees(children.into_iter().map(Some).collect());
+        let entry = Rc::into_inner(entry).expect("handles only hold weak references");
+        Some(entry.into_inner().value)
+    }
+
+    /// Moves all elements of `other` into `self` in O(log n). Handles into `other` stay
+    /// valid and now refer to `self`.
+    pub fn meld(&mut self, other: Self) {
+        let BinomialHeap { trees, len, .. } = other;
+        self.len += len;
+        self.merge_trees(trees);
+    }
+
+    /// Replaces the value behind `handle` with `value` in O(log n). Returns `false` and
+    /// leaves the heap unchanged if `value` would pop after the current value, or if that
+    /// value has already been popped. `handle` must come from this heap.
+    pub fn decrease_key(&mut self, handle: &Handle<T>, value: T) -> bool {
+        let entry = match handle.0.upgrade() {
+            Some(entry) => entry,
+            None => return false,
+        };
+        if let Ordering::Greater = self.cmp.compare(&value, &entry.borrow().value) {
+            return false;
+        }
+        entry.borrow_mut().value = value;
+
+        // 节点结构不变，只沿父节点往上交换 entry
+        let mut node = entry
+            .borrow()
+            .node
+            .upgrade()
+            .expect("queued entry has a node");
+        loop {
+            let parent = node.borrow().parent.upgrade();
+            let parent = match parent {
+                Some(parent) => parent,
+                None => break,
+            };
+            let ordering = self.cmp_entry(&node.borrow().entry, &parent.borrow().entry);
+            if ordering != Ordering::Less {
+                break;
+            }
+            {
+                let mut child_node = node.borrow_mut();
+                let mut parent_node = parent.borrow_mut();
+                mem::swap(&mut child_node.entry, &mut parent_node.entry);
+                child_node.entry.borrow_mut().node = Rc::downgrade(&node);
+                parent_node.entry.borrow_mut().node = Rc::downgrade(&parent);
+            }
+            node = parent;
+        }
+        let is_top = self
+            .top
+            .as_ref()
+            .is_none_or(|top| self.cmp_entry(&entry, top) == Ordering::Less);
+        if is_top {
+            self.top = Some(entry);
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.trees.clear();
+        self.top = None;
+        self.len = 0;
+    }
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary_heap::heap_test_suite;
+    use crate::binomial_heap::{BinomialHeap, Handle};
+
+    heap_test_suite!(BinomialHeap, Handle);
+
+    #[test]
+    fn test_tree_orders_follow_len() {
+        let mut heap = BinomialHeap::new();
+        for val in 0..1_000 {
+            heap.push(val);
+            let orders: Vec<_> = heap.trees.iter().map(Option::is_some).collect();
+            let bits: Vec<_> = (0..orders.len())
+                .map(|k| heap.len() >> k & 1 == 1)
+                .collect();
+            assert_eq!(orders, bits);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+// 分块布隆过滤器（Putze 等，Cache-, Hash- and Space-Efficient Bloom Filters）。
+//
+// 位数组切成 64 字节（一个缓存行）的块，h1 选块，k 个位置都落在这个块里：
+// block = h1(x) mod B，gi(x) = lo(h2) + i·hi(h2) mod 512
+// 每次查询只访问一个缓存行，代价是各块的填充不均匀，同样的 m、k 下误差率会比经典布隆过滤器略高。
+
+use crate::bloom_filter::{get_index, optimal_bits_size, optimal_hash_fn_count, DoubleHasher};
+use rand::random;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+const WORDS_PER_BLOCK: usize = 8;
+const WORD_BITS: usize = u64::BITS as usize;
+const BLOCK_BITS: usize = WORDS_PER_BLOCK * WORD_BITS;
+
+#[repr(align(64))]
+#[derive(Clone, Copy, Default)]
+struct Block([u64; WORDS_PER_BLOCK]);
+
+#[allow(unused)]
+pub struct BlockedBloomFilter<T: ?Sized> {
+    blocks: Vec<Block>,
+    hash_fn_count: usize,
+    hasher: DoubleHasher,
+    len: usize,
+    _phantom: PhantomData<T>,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> BlockedBloomFilter<T> {
+    pub fn new(cap: usize, err_rate: f64) -> Self {
+        Self::with_seeds(cap, err_rate, [random(), random()])
+    }
+
+    pub fn with_seeds(cap: usize, err_rate: f64, seeds: [u64; 2]) -> Self {
+        let block_count = optimal_bits_size(cap, err_rate).div_ceil(BLOCK_BITS);
+        let hash_fn_count = optimal_hash_fn_count(block_count * BLOCK_BITS, cap);
+        BlockedBloomFilter {
+            blocks: vec![Block::default(); block_count],
+            hash_fn_count,
+            hasher: DoubleHasher::with_seeds(seeds),
+            len: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// 返回块下标，以及块内做双重哈希用的 (lo, hi)；块大小是 2 的幂，步长取奇数才能走遍所有位置。
+    fn locate(&self, value: &T) -> (usize, (u64, u64)) {
+        let (hash1, hash2) = self.hasher.cal_hash(value);
+        let block = (hash1 % self.blocks.len() as u64) as usize;
+        (block, (hash2 & 0xffff_ffff, (hash2 >> 32) | 1))
+    }
+
+    pub fn insert(&mut self, value: &T) {
+        let (block, hash_tuple) = self.locate(value);
+        let words = &mut self.blocks[block].0;
+        for fn_i in 0..self.hash_fn_count {
+            let index = get_index(hash_tuple, fn_i, BLOCK_BITS);
+            words[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+        }
+        self.len += 1;
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let (block, hash_tuple) = self.locate(value);
+        let words = &self.blocks[block].0;
+        (0..self.hash_fn_count).all(|fn_i| {
+            let index = get_index(hash_tuple, fn_i, BLOCK_BITS);
+            words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+        })
+    }
+
+    /// Number of `insert` calls so far, duplicates included.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn bits_len(&self) -> usize {
+        self.blocks.len() * BLOCK_BITS
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn hash_fn_count(&self) -> usize {
+        self.hash_fn_count
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.blocks.len() * size_of::<Block>()
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.0)
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn fill_ratio(&self) -> f64 {
+        self.count_ones() as f64 / self.bits_len() as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::blocked_bloom_filter::{Block, BlockedBloomFilter};
+    use crate::bloom_filter::BloomFilter;
+    use rand::rngs::StdRng;
+    use rand::{thread_rng, Rng, SeedableRng};
+    use std::collections::HashSet;
+    use std::time::Instant;
+
+    // 按生成顺序返回互不相同的 key，种子固定时结果可复现
+    fn random_keys(count: usize, rng: &mut impl Rng) -> Vec<u64> {
+        let mut seen = HashSet::with_capacity(count);
+        let mut keys = Vec::with_capacity(count);
+        while keys.len() < count {
+            let key = rng.gen();
+            if seen.insert(key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn test_block_is_a_cache_line() {
+        assert_eq!(size_of::<Block>(), 64);
+        assert_eq!(align_of::<Block>(), 64);
+        let filter = BlockedBloomFilter::<u64>::new(10_000, 0.01);
+        assert_eq!(filter.blocks.as_ptr() as usize % 64, 0);
+    }
+
+    #[test]
+    fn test_insert_and_query() {
+        let cap = 100_000;
+        let mut filter = BlockedBloomFilter::new(cap, 0.01);
+        for key in 0..cap as u64 {
+            filter.insert(&key);
+        }
+        assert_eq!(filter.len(), cap);
+        assert!((0..cap as u64).all(|key| filter.contains(&key)));
+        assert!(filter.bits_len() >= BloomFilter::<u64>::new(cap, 0.01).bits_len());
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let cap = 200_000;
+        // 种子固定，结果是确定的：ε = 0.1% 时经典过滤器的实测值本身就在 ε 上下波动
+        let keys = random_keys(2 * cap, &mut StdRng::seed_from_u64(0x5eed));
+        let (inserted, probes) = keys.split_at(cap);
+        for err_rate in [0.05, 0.01, 0.001] {
+            let mut classic = BloomFilter::with_seeds(cap, err_rate, [1, 2]);
+            let mut blocked = BlockedBloomFilter::with_seeds(cap, err_rate, [1, 2]);
+            for key in inserted {
+                classic.insert(key);
+                blocked.insert(key);
+            }
+            let measure = |contains: &dyn Fn(&u64) -> bool| {
+                probes.iter().filter(|key| contains(key)).count() as f64 / probes.len() as f64
+            };
+            let classic_fpr = measure(&|key| classic.contains(key));
+            let blocked_fpr = measure(&|key| blocked.contains(key));
+            // 分块带来的误差率损失在 k 较小时不大，k 越大损失越明显（ε = 0.1% 时约为 3 倍）
+            assert!(
+                classic_fpr < err_rate * 1.1,
+                "ε = {err_rate}: classic fpr {classic_fpr}"
+            );
+            assert!(
+                blocked_fpr >= classic_fpr * 0.9,
+                "ε = {err_rate}: classic fpr {classic_fpr}, blocked fpr {blocked_fpr}"
+            );
+            assert!(
+                blocked_fpr < err_rate * 4f64,
+                "ε = {err_rate}: blocked fpr {blocked_fpr}"
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with cargo test --release -- --ignored"]
+    fn bench_blocked_vs_classic() {
+        let cap = 500_000;
+        let keys = random_keys(2 * cap, &mut thread_rng());
+        let (inserted, probes) = keys.split_at(cap);
+
+        let mut classic = BloomFilter::new(cap, 0.01);
+        let start = Instant::now();
+        for key in inserted {
+            classic.insert(key);
+        }
+        let classic_insert = start.elapsed();
+        let start = Instant::now();
+        let classic_hits = probes.iter().filter(|key| classic.contains(key)).count();
+        let classic_contains = start.elapsed();
+
+        let mut blocked = BlockedBloomFilter::new(cap, 0.01);
+        let start = Instant::now();
+        for key in inserted {
+            blocked.insert(key);
+        }
+        let blocked_insert = start.elapsed();
+        let start = Instant::now();
+        let blocked_hits = probes.iter().filter(|key| blocked.contains(key)).count();
+        let blocked_contains = start.elapsed();
+
+        let throughput = |elapsed: std::time::Duration| cap as f64 / elapsed.as_secs_f64();
+        println!(
+            "classic: insert {:>12.0} ops/s, contains {:>12.0} ops/s, fpr {}",
+            throughput(classic_insert),
+            throughput(classic_contains),
+            classic_hits as f64 / cap as f64
+        );
+        println!(
+            "blocked: insert {:>12.0} ops/s, contains {:>12.0} ops/s, fpr {}",
+            throughput(blocked_insert),
+            throughput(blocked_contains),
+            blocked_hits as f64 / cap as f64
+        );
+    }
+}
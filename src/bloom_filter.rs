@@ -1,50 +1,289 @@
 //已知布隆过滤器长度为 n ，在可容忍的误差率为 ε 的情况下，此时最佳的存储个数为 m: m = − nlnε / (ln2)2
 //
 // 而此时需要的哈希函数个数 k 为:
-// k = − lnε / ln2 = log2ε
+// k = (m / n)ln2 = − lnε / ln2 = − log2ε
 //
-// 假如容忍的误差率 ε = 8% ，那么 k = 3，k 越大代表误差率越大。在不改变容错率的情
+// 假如容忍的误差率 ε = 8% ，那么 k = 3，k 越大代表误差率越小。在不改变容错率的情
 // 况下，可以组合迭代次数和两个基本哈希函数来模拟 k 个哈希函数。
 // gi(x) = h1(x) + ih2(x)
+//
+// h1 与 h2 必须相互独立，否则 gi(x) 会退化成同一个位置，所以两个哈希器使用不同的随机种子。
+//
+// 位数组按 64 位一组打包存放在 u64 中，每个位置只占 1 bit。
+//
+// 哈希函数可以通过 BuildHasher 替换（比如更快的 FxHasher），默认使用 StableHasher，保证同样的
+// 种子在任何进程里算出同样的位置，所以只有默认哈希的过滤器才能持久化。并集、交集同理：
+// 其他 BuildHasher（比如 RandomState）带着各自的随机状态，无法判断两个过滤器的位置是否一致。
+// 持久化格式（小端）：
+// "BLMF" | 版本 u16 | k u32 | m u64 | 插入次数 u64 | 种子 2×u64 | 位数组 ⌈m/64⌉×u64 | CRC-32
 
-use std::collections::hash_map::DefaultHasher;
+use crate::codec::{DecodeError, Decoder, Encoder};
+use crate::hasher::StableHasher;
+use rand::random;
+use std::error::Error;
 use std::f64::consts::LN_2;
-use std::hash::{Hash, Hasher};
+use std::fmt::{Display, Formatter};
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::io;
+use std::io::{Read, Write};
 use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ops::{BitAndAssign, BitOrAssign};
+
+const BLOCK_BITS: usize = u64::BITS as usize;
+const MAGIC: &[u8; 4] = b"BLMF";
+const FORMAT_VERSION: u16 = 1;
+
+/// m = −n·lnε / (ln2)²
+pub(crate) fn optimal_bits_size(cap: usize, err_rate: f64) -> usize {
+    assert!(
+        err_rate > 0f64 && err_rate < 1f64,
+        "err_rate must be in (0, 1), got {err_rate}"
+    );
+    let bits_size = (-(cap.max(1) as f64) * err_rate.ln() / LN_2.powi(2)).ceil();
+    (bits_size as usize).max(1)
+}
+
+/// k = (m / n)·ln2
+pub(crate) fn optimal_hash_fn_count(bits_size: usize, cap: usize) -> usize {
+    let hash_fn_count = (bits_size as f64 / cap.max(1) as f64 * LN_2).round();
+    (hash_fn_count as usize).max(1)
+}
+
+/// gi(x) = h1(x) + i·h2(x) mod len
+pub(crate) fn get_index(hash_tuple: (u64, u64), fn_i: usize, len: usize) -> usize {
+    (hash_tuple
+        .0
+        .wrapping_add((fn_i as u64).wrapping_mul(hash_tuple.1))
+        % len as u64) as usize
+}
+
+/// 同一个 BuildHasher 分别先写入两个不同的种子，算出相互独立的 h1(x) 和 h2(x)。
+#[derive(Clone)]
+pub(crate) struct DoubleHasher<S = BuildHasherDefault<StableHasher>> {
+    build_hasher: S,
+    seeds: [u64; 2],
+}
 
 #[allow(unused)]
-struct BloomFilter<T: ?Sized> {
-    bits: Vec<bool>,
+impl DoubleHasher {
+    pub fn new() -> Self {
+        Self::with_seeds([random(), random()])
+    }
+
+    pub fn with_seeds(seeds: [u64; 2]) -> Self {
+        Self::with_hasher(seeds, BuildHasherDefault::default())
+    }
+}
+
+#[allow(unused)]
+impl<S: BuildHasher> DoubleHasher<S> {
+    pub fn with_hasher(seeds: [u64; 2], build_hasher: S) -> Self {
+        DoubleHasher {
+            build_hasher,
+            seeds,
+        }
+    }
+
+    pub fn seeds(&self) -> [u64; 2] {
+        self.seeds
+    }
+
+    pub fn cal_hash<T: ?Sized + Hash>(&self, value: &T) -> (u64, u64) {
+        let [hash1, hash2] = self.seeds.map(|seed| {
+            let mut hasher = self.build_hasher.build_hasher();
+            hasher.write_u64(seed);
+            value.hash(&mut hasher);
+            hasher.finish()
+        });
+        (hash1, hash2)
+    }
+}
+
+#[allow(unused)]
+pub struct BloomFilter<T: ?Sized, S = BuildHasherDefault<StableHasher>> {
+    bits: Vec<u64>,
+    bits_len: usize,
     hash_fn_count: usize,
-    hasher: [DefaultHasher; 2],
+    hasher: DoubleHasher<S>,
+    len: usize,
     _phantom: PhantomData<T>,
 }
 
 #[allow(unused)]
 impl<T: ?Sized + Hash> BloomFilter<T> {
     pub fn new(cap: usize, err_rate: f64) -> Self {
-        let ln2_pow = LN_2.powf(2f64);
-        let bits_size = (-1f64 * cap as f64 * ln2_pow / err_rate.ln()) as usize;
-        let hash_fn_count = (-1f64 * err_rate.log2()) as usize;
-        let hasher = [DefaultHasher::new(), DefaultHasher::new()];
+        Self::with_seeds(cap, err_rate, [random(), random()])
+    }
+
+    /// Same as `new`, but with fixed hash seeds so that separately built filters
+    /// map every value to the same bits.
+    pub fn with_seeds(cap: usize, err_rate: f64, seeds: [u64; 2]) -> Self {
+        Self::with_seeds_and_hasher(cap, err_rate, seeds, BuildHasherDefault::default())
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = Encoder::new(writer, MAGIC, FORMAT_VERSION)?;
+        encoder.write_u32(self.hash_fn_count as u32)?;
+        encoder.write_u64(self.bits_len as u64)?;
+        encoder.write_u64(self.len as u64)?;
+        for seed in self.seeds() {
+            encoder.write_u64(seed)?;
+        }
+        for &block in &self.bits {
+            encoder.write_u64(block)?;
+        }
+        encoder.finish()
+    }
+
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, DecodeError> {
+        let (mut decoder, version) = Decoder::new(reader, MAGIC)?;
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let hash_fn_count = decoder.read_u32()? as usize;
+        let bits_len = usize::try_from(decoder.read_u64()?)
+            .map_err(|_| DecodeError::Corrupt("bit count overflows usize"))?;
+        let len = decoder.read_u64()? as usize;
+        let seeds = [decoder.read_u64()?, decoder.read_u64()?];
+        if hash_fn_count == 0 {
+            return Err(DecodeError::Corrupt("zero hash functions"));
+        }
+        if bits_len == 0 {
+            return Err(DecodeError::Corrupt("empty bit array"));
+        }
+        // 逐块读取，长度字段损坏时只会读到 EOF，而不会一次分配过大的内存
+        let mut bits = Vec::new();
+        for _ in 0..bits_len.div_ceil(BLOCK_BITS) {
+            bits.push(decoder.read_u64()?);
+        }
+        decoder.finish()?;
+        let used = bits_len % BLOCK_BITS;
+        if used != 0 && bits.last().is_some_and(|&block| block >> used != 0) {
+            return Err(DecodeError::Corrupt("bits set past the end of the array"));
+        }
+        Ok(BloomFilter {
+            bits,
+            bits_len,
+            hash_fn_count,
+            hasher: DoubleHasher::with_seeds(seeds),
+            len,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn check_compatible(&self, other: &Self) -> Result<(), IncompatibleError> {
+        if self.bits_len != other.bits_len {
+            Err(IncompatibleError::BitsLen(self.bits_len, other.bits_len))
+        } else if self.hash_fn_count != other.hash_fn_count {
+            Err(IncompatibleError::HashFnCount(
+                self.hash_fn_count,
+                other.hash_fn_count,
+            ))
+        } else if self.seeds() != other.seeds() {
+            Err(IncompatibleError::Seeds)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Every value of A ∪ B is contained afterwards, with the same error rate as
+    /// inserting both sides into one filter.
+    pub fn union_with(&mut self, other: &Self) -> Result<(), IncompatibleError> {
+        self.check_compatible(other)?;
+        for (block, other_block) in self.bits.iter_mut().zip(&other.bits) {
+            *block |= other_block;
+        }
+        // 位全部置 1 时估计值为 +inf，所以不能超过两边插入次数之和
+        let bound = self.len.saturating_add(other.len);
+        self.len = self.estimate_len().min(bound as f64).round() as usize;
+        Ok(())
+    }
+
+    /// Every value of A ∩ B is contained afterwards, but the error rate may be higher
+    /// than that of a filter built from A ∩ B directly.
+    pub fn intersect_with(&mut self, other: &Self) -> Result<(), IncompatibleError> {
+        self.check_compatible(other)?;
+        for (block, other_block) in self.bits.iter_mut().zip(&other.bits) {
+            *block &= other_block;
+        }
+        let bound = self.len.min(other.len);
+        self.len = self.estimate_len().min(bound as f64).round() as usize;
+        Ok(())
+    }
+
+    pub fn union(&self, other: &Self) -> Result<Self, IncompatibleError> {
+        let mut result = self.clone();
+        result.union_with(other)?;
+        Ok(result)
+    }
+
+    pub fn intersect(&self, other: &Self) -> Result<Self, IncompatibleError> {
+        let mut result = self.clone();
+        result.intersect_with(other)?;
+        Ok(result)
+    }
+
+    pub fn estimate_union_len(&self, other: &Self) -> Result<f64, IncompatibleError> {
+        self.check_compatible(other)?;
+        let ones = self
+            .bits
+            .iter()
+            .zip(&other.bits)
+            .map(|(block, other_block)| (block | other_block).count_ones() as usize)
+            .sum();
+        Ok(Self::estimate_from_ones(
+            ones,
+            self.bits_len,
+            self.hash_fn_count,
+        ))
+    }
+
+    /// |A ∩ B| ≈ |A| + |B| − |A ∪ B|
+    pub fn estimate_intersection_len(&self, other: &Self) -> Result<f64, IncompatibleError> {
+        let union_len = self.estimate_union_len(other)?;
+        Ok((self.estimate_len() + other.estimate_len() - union_len).max(0f64))
+    }
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash, S: BuildHasher> BloomFilter<T, S> {
+    pub fn with_hasher(cap: usize, err_rate: f64, build_hasher: S) -> Self {
+        Self::with_seeds_and_hasher(cap, err_rate, [random(), random()], build_hasher)
+    }
+
+    pub fn with_seeds_and_hasher(
+        cap: usize,
+        err_rate: f64,
+        seeds: [u64; 2],
+        build_hasher: S,
+    ) -> Self {
+        let bits_size = optimal_bits_size(cap, err_rate);
+        let hash_fn_count = optimal_hash_fn_count(bits_size, cap);
         BloomFilter {
-            bits: vec![false; bits_size],
+            bits: vec![0; bits_size.div_ceil(BLOCK_BITS)],
+            bits_len: bits_size,
             hash_fn_count,
-            hasher,
+            hasher: DoubleHasher::with_hasher(seeds, build_hasher),
+            len: 0,
             _phantom: PhantomData,
         }
     }
 
     fn cal_hash(&self, value: &T) -> (u64, u64) {
-        let [mut hash1, mut hash2] = self.hasher.clone();
-        value.hash(&mut hash1);
-        value.hash(&mut hash2);
-        (hash1.finish(), hash2.finish())
+        self.hasher.cal_hash(value)
     }
 
     fn get_index(&self, hash_tuple: (u64, u64), fn_i: usize) -> usize {
-        (hash_tuple.0 as usize).wrapping_add(fn_i.wrapping_mul(hash_tuple.1 as usize))
-            % self.bits.len()
+        get_index(hash_tuple, fn_i, self.bits_len)
+    }
+
+    fn set_bit(&mut self, index: usize) {
+        self.bits[index / BLOCK_BITS] |= 1 << (index % BLOCK_BITS);
+    }
+
+    fn get_bit(&self, index: usize) -> bool {
+        self.bits[index / BLOCK_BITS] & (1 << (index % BLOCK_BITS)) != 0
     }
 
     pub fn insert(&mut self, value: &T) {
@@ -52,22 +291,151 @@ impl<T: ?Sized + Hash> BloomFilter<T> {
 
         for fn_i in 0..self.hash_fn_count {
             let index = self.get_index(hash_tuple, fn_i);
-            self.bits[index] = true;
+            self.set_bit(index);
         }
+        self.len += 1;
     }
 
     pub fn contains(&self, value: &T) -> bool {
         let hash_tuple = self.cal_hash(value);
-        (0..self.hash_fn_count).all(|fn_i| self.bits[self.get_index(hash_tuple, fn_i)])
+        (0..self.hash_fn_count).all(|fn_i| self.get_bit(self.get_index(hash_tuple, fn_i)))
+    }
+
+    /// Number of `insert` calls so far, duplicates included.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forgets every value, keeping the size and hash seeds.
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.len = 0;
+    }
+
+    pub fn bits_len(&self) -> usize {
+        self.bits_len
+    }
+
+    /// Heap memory used by the bit array, in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.bits.len() * size_of::<u64>()
+    }
+
+    /// Number of bits currently set.
+    pub fn count_ones(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|block| block.count_ones() as usize)
+            .sum()
+    }
+
+    /// Fraction of bits currently set.
+    pub fn fill_ratio(&self) -> f64 {
+        self.count_ones() as f64 / self.bits_len as f64
+    }
+
+    pub fn hash_fn_count(&self) -> usize {
+        self.hash_fn_count
+    }
+
+    pub fn seeds(&self) -> [u64; 2] {
+        self.hasher.seeds()
+    }
+
+    /// Expected false positive rate at the current fill level: (1 − e^(−kn/m))^k
+    pub fn expected_false_positive_rate(&self) -> f64 {
+        let k = self.hash_fn_count as f64;
+        let exponent = -k * self.len as f64 / self.bits_len as f64;
+        (1f64 - exponent.exp()).powf(k)
+    }
+
+    /// False positive rate estimated from the bits actually set: fill_ratio^k
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        self.fill_ratio().powi(self.hash_fn_count as i32)
+    }
+
+    /// Swamidass–Baldi estimate of the distinct values inserted: −(m / k)·ln(1 − X / m),
+    /// where X is the number of bits set.
+    pub fn estimate_len(&self) -> f64 {
+        Self::estimate_from_ones(self.count_ones(), self.bits_len, self.hash_fn_count)
+    }
+
+    fn estimate_from_ones(ones: usize, bits_len: usize, hash_fn_count: usize) -> f64 {
+        let m = bits_len as f64;
+        -(m / hash_fn_count as f64) * (1f64 - ones as f64 / m).ln()
+    }
+}
+
+impl<T: ?Sized, S: Clone> Clone for BloomFilter<T, S> {
+    fn clone(&self) -> Self {
+        BloomFilter {
+            bits: self.bits.clone(),
+            bits_len: self.bits_len,
+            hash_fn_count: self.hash_fn_count,
+            hasher: self.hasher.clone(),
+            len: self.len,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Panics if the two filters were built with different parameters, see `union_with`.
+impl<T: ?Sized + Hash> BitOrAssign<&BloomFilter<T>> for BloomFilter<T> {
+    fn bitor_assign(&mut self, rhs: &BloomFilter<T>) {
+        if let Err(err) = self.union_with(rhs) {
+            panic!("cannot union bloom filters: {err}");
+        }
+    }
+}
+
+/// Panics if the two filters were built with different parameters, see `intersect_with`.
+impl<T: ?Sized + Hash> BitAndAssign<&BloomFilter<T>> for BloomFilter<T> {
+    fn bitand_assign(&mut self, rhs: &BloomFilter<T>) {
+        if let Err(err) = self.intersect_with(rhs) {
+            panic!("cannot intersect bloom filters: {err}");
+        }
+    }
+}
+
+/// Set operations need both filters to map every value to the same bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncompatibleError {
+    BitsLen(usize, usize),
+    HashFnCount(usize, usize),
+    Seeds,
+}
+
+impl Display for IncompatibleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IncompatibleError::BitsLen(left, right) => {
+                write!(f, "bit array sizes differ: {left} vs {right}")
+            }
+            IncompatibleError::HashFnCount(left, right) => {
+                write!(f, "hash function counts differ: {left} vs {right}")
+            }
+            IncompatibleError::Seeds => write!(f, "hash seeds differ"),
+        }
     }
 }
 
+impl Error for IncompatibleError {}
+
 #[cfg(test)]
 mod test {
-    use crate::bloom_filter::BloomFilter;
+    use crate::bloom_filter::{BloomFilter, IncompatibleError};
+    use crate::codec::DecodeError;
+    use crate::hasher::{FxBuildHasher, StableBuildHasher};
     use rand::distributions::Alphanumeric;
-    use rand::{thread_rng, Rng};
+    use rand::{random, thread_rng, Rng};
+    use std::collections::hash_map::{DefaultHasher, RandomState};
     use std::collections::HashSet;
+    use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+    use std::time::Instant;
 
     #[test]
     fn test_insert_and_query() {
@@ -84,6 +452,339 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_fill_ratio() {
+        let cap = 100_000;
+        let mut filter = BloomFilter::new(cap, 0.01);
+        // 一个 bit 一个位置，按 u64 整块分配
+        assert_eq!(filter.size_in_bytes(), filter.bits_len().div_ceil(64) * 8);
+        assert_eq!(filter.count_ones(), 0);
+        assert_eq!(filter.fill_ratio(), 0f64);
+        for key in 0..cap as u64 {
+            filter.insert(&key);
+        }
+        assert!(filter.count_ones() <= cap * filter.hash_fn_count());
+        // 插满容量时置 1 的比例约为 1 − e^(−kn/m)，最佳参数下接近一半
+        let k = filter.hash_fn_count() as f64;
+        let expected_fill = 1f64 - (-k * cap as f64 / filter.bits_len() as f64).exp();
+        assert!((filter.fill_ratio() - expected_fill).abs() < 0.01);
+        let expected = filter.expected_false_positive_rate();
+        let estimated = filter.estimated_false_positive_rate();
+        assert!((estimated - expected).abs() < expected * 0.1);
+    }
+
+    #[test]
+    fn test_optimal_sizing() {
+        // ε = 1% → m/n ≈ 9.59, k ≈ 6.64
+        let filter = BloomFilter::<str>::new(1_000, 0.01);
+        assert_eq!(filter.bits_len(), 9586);
+        assert_eq!(filter.hash_fn_count(), 7);
+        assert_ne!(filter.seeds()[0], filter.seeds()[1]);
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let cap = 1_000_000;
+        let err_rate = 0.01;
+        let mut filter = BloomFilter::new(cap, err_rate);
+        let mut inserted = HashSet::with_capacity(cap);
+        while inserted.len() < cap {
+            let key: u64 = random();
+            if inserted.insert(key) {
+                filter.insert(&key);
+            }
+        }
+        assert_eq!(filter.len(), cap);
+        let expected = filter.expected_false_positive_rate();
+        assert!((expected - err_rate).abs() < err_rate * 0.05);
+
+        let mut false_positives = 0;
+        let mut probes = 0;
+        while probes < cap {
+            let key: u64 = random();
+            if inserted.contains(&key) {
+                continue;
+            }
+            probes += 1;
+            if filter.contains(&key) {
+                false_positives += 1;
+            }
+        }
+        let measured = false_positives as f64 / probes as f64;
+        assert!(
+            (measured - expected).abs() < expected * 0.1,
+            "measured fpr {measured} too far from expected {expected}"
+        );
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let mut filter = BloomFilter::<str>::new(10_000, 0.01);
+        let words: Vec<String> = (0..5_000).map(|i| format!("word-{i}")).collect();
+        for word in &words {
+            filter.insert(word);
+        }
+        let mut buf = Vec::new();
+        filter.write_to(&mut buf).unwrap();
+        assert_eq!(buf.len(), 4 + 2 + 4 + 8 * 4 + filter.size_in_bytes() + 4);
+
+        let loaded = BloomFilter::<str>::read_from(buf.as_slice()).unwrap();
+        assert_eq!(loaded.bits_len(), filter.bits_len());
+        assert_eq!(loaded.hash_fn_count(), filter.hash_fn_count());
+        assert_eq!(loaded.seeds(), filter.seeds());
+        assert_eq!(loaded.len(), filter.len());
+        assert_eq!(loaded.count_ones(), filter.count_ones());
+        assert!(words.iter().all(|word| loaded.contains(word)));
+        assert!((0..10_000)
+            .map(|i| format!("other-{i}"))
+            .all(|word| loaded.contains(&word) == filter.contains(&word)));
+    }
+
+    #[test]
+    fn test_stable_across_processes() {
+        // 固定种子的同一个元素总是落在同样的位上，这段字节在任何进程里都应该一样
+        let mut buf = Vec::new();
+        let mut filter = BloomFilter::<str>::with_seeds(10, 0.01, [1, 2]);
+        filter.insert("hello");
+        filter.write_to(&mut buf).unwrap();
+        let loaded = BloomFilter::<str>::read_from(buf.as_slice()).unwrap();
+        assert!(loaded.contains("hello"));
+        assert_eq!(loaded.bits, filter.bits);
+        assert_eq!(filter.bits, [9380342792192, 8945664]);
+    }
+
+    #[test]
+    fn test_read_rejects_bad_input() {
+        let mut filter = BloomFilter::<u64>::new(1_000, 0.01);
+        for key in 0..1_000u64 {
+            filter.insert(&key);
+        }
+        let mut buf = Vec::new();
+        filter.write_to(&mut buf).unwrap();
+
+        let mut bad_magic = buf.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            BloomFilter::<u64>::read_from(bad_magic.as_slice()),
+            Err(DecodeError::BadMagic(_))
+        ));
+
+        let mut bad_version = buf.clone();
+        bad_version[4] = 2;
+        assert!(matches!(
+            BloomFilter::<u64>::read_from(bad_version.as_slice()),
+            Err(DecodeError::UnsupportedVersion(2))
+        ));
+
+        let mut flipped = buf.clone();
+        flipped[100] ^= 0x10;
+        assert!(matches!(
+            BloomFilter::<u64>::read_from(flipped.as_slice()),
+            Err(DecodeError::ChecksumMismatch { .. })
+        ));
+
+        let truncated = &buf[..buf.len() - 10];
+        assert!(matches!(
+            BloomFilter::<u64>::read_from(truncated),
+            Err(DecodeError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_union_and_intersect() {
+        let seeds = [7, 11];
+        let mut left = BloomFilter::with_seeds(100_000, 0.01, seeds);
+        let mut right = BloomFilter::with_seeds(100_000, 0.01, seeds);
+        for key in 0..60_000u64 {
+            left.insert(&key);
+        }
+        for key in 40_000..100_000u64 {
+            right.insert(&key);
+        }
+
+        let union = left.union(&right).unwrap();
+        assert!((0..100_000u64).all(|key| union.contains(&key)));
+        let intersection = left.intersect(&right).unwrap();
+        assert!((40_000..60_000u64).all(|key| intersection.contains(&key)));
+        let outside = (100_000..200_000u64)
+            .filter(|key| intersection.contains(key))
+            .count();
+        assert!(outside < 2_000);
+
+        let mut merged = left.clone();
+        merged |= &right;
+        assert_eq!(merged.bits, union.bits);
+        let mut common = left.clone();
+        common &= &right;
+        assert_eq!(common.bits, intersection.bits);
+    }
+
+    #[test]
+    fn test_estimate_len() {
+        let seeds = [7, 11];
+        let mut left = BloomFilter::with_seeds(100_000, 0.01, seeds);
+        let mut right = BloomFilter::with_seeds(100_000, 0.01, seeds);
+        for key in 0..60_000u64 {
+            left.insert(&key);
+            // 重复插入不影响估计值
+            left.insert(&key);
+        }
+        for key in 40_000..100_000u64 {
+            right.insert(&key);
+        }
+        let close = |estimate: f64, actual: f64| (estimate - actual).abs() < actual * 0.03;
+        assert!(close(left.estimate_len(), 60_000f64));
+        assert!(close(right.estimate_len(), 60_000f64));
+        assert!(close(left.estimate_union_len(&right).unwrap(), 100_000f64));
+        assert!(close(
+            left.estimate_intersection_len(&right).unwrap(),
+            20_000f64
+        ));
+        let union = left.union(&right).unwrap();
+        assert!(close(union.len() as f64, 100_000f64));
+    }
+
+    // 位全部置 1 时估计值是无穷大，合并后的插入次数仍要是个有限值，之后还能继续插入
+    #[test]
+    fn test_union_of_saturated_filters() {
+        let seeds = [3, 5];
+        let mut left = BloomFilter::with_seeds(10, 0.1, seeds);
+        let mut right = BloomFilter::with_seeds(10, 0.1, seeds);
+        for key in 0..1_000u64 {
+            left.insert(&key);
+            right.insert(&(key + 1_000));
+        }
+        assert_eq!(left.count_ones(), left.bits_len());
+        assert_eq!(left.estimate_len(), f64::INFINITY);
+
+        let mut union = left.union(&right).unwrap();
+        assert_eq!(union.len(), 2_000);
+        union.insert(&2_000);
+        assert_eq!(union.len(), 2_001);
+        let mut intersection = left.intersect(&right).unwrap();
+        assert_eq!(intersection.len(), 1_000);
+        intersection.insert(&2_000);
+        assert_eq!(intersection.len(), 1_001);
+    }
+
+    #[test]
+    fn test_incompatible_filters() {
+        let filter = BloomFilter::<u64>::with_seeds(1_000, 0.01, [1, 2]);
+        assert_eq!(
+            filter
+                .union(&BloomFilter::with_seeds(1_000, 0.01, [1, 3]))
+                .err(),
+            Some(IncompatibleError::Seeds)
+        );
+        let bigger = BloomFilter::with_seeds(2_000, 0.01, [1, 2]);
+        assert_eq!(
+            filter.intersect(&bigger).err(),
+            Some(IncompatibleError::BitsLen(
+                filter.bits_len(),
+                bigger.bits_len()
+            ))
+        );
+        assert!(filter.estimate_intersection_len(&bigger).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot union bloom filters")]
+    fn test_union_operator_panics_on_mismatch() {
+        let mut filter = BloomFilter::<u64>::new(1_000, 0.01);
+        filter |= &BloomFilter::new(1_000, 0.01);
+    }
+
+    fn measure_fpr<S: BuildHasher>(build_hasher: S) -> f64 {
+        let cap = 100_000;
+        let mut filter = BloomFilter::with_hasher(cap, 0.01, build_hasher);
+        // 连续整数是最容易暴露哈希质量问题的 key
+        for key in 0..cap as u64 {
+            filter.insert(&key);
+        }
+        let false_positives = (cap as u64..2 * cap as u64)
+            .filter(|key| filter.contains(key))
+            .count();
+        false_positives as f64 / cap as f64
+    }
+
+    #[test]
+    fn test_pluggable_hasher() {
+        for fpr in [
+            measure_fpr(StableBuildHasher::default()),
+            measure_fpr(FxBuildHasher::default()),
+            measure_fpr(RandomState::new()),
+        ] {
+            assert!(fpr < 0.012, "fpr {fpr} too high");
+        }
+    }
+
+    // 只有高位在变的整数：哈希没有收尾混合时 h1、h2 也只有高位不同，两者高度相关
+    fn measure_structured_fpr<S: BuildHasher>(build_hasher: S) -> f64 {
+        let cap = 100_000;
+        let mut filter = BloomFilter::with_hasher(cap, 0.01, build_hasher);
+        for key in 0..cap as u64 {
+            filter.insert(&(key << 46));
+        }
+        let false_positives = (cap as u64..2 * cap as u64)
+            .filter(|key| filter.contains(&(key << 46)))
+            .count();
+        false_positives as f64 / cap as f64
+    }
+
+    #[test]
+    fn test_structured_keys() {
+        for fpr in [
+            measure_structured_fpr(StableBuildHasher::default()),
+            measure_structured_fpr(FxBuildHasher::default()),
+            measure_structured_fpr(RandomState::new()),
+        ] {
+            assert!(fpr < 0.015, "fpr {fpr} too high");
+        }
+    }
+
+    fn bench_hasher<S: BuildHasher, K: Hash>(name: &str, build_hasher: S, keys: &[K]) {
+        let mut filter = BloomFilter::with_hasher(keys.len(), 0.01, build_hasher);
+        let start = Instant::now();
+        for key in keys {
+            filter.insert(key);
+        }
+        let insert = start.elapsed();
+        let start = Instant::now();
+        let hits = keys.iter().filter(|key| filter.contains(key)).count();
+        let contains = start.elapsed();
+        assert_eq!(hits, keys.len());
+        let throughput = |elapsed: std::time::Duration| keys.len() as f64 / elapsed.as_secs_f64();
+        println!(
+            "{name:>8}: insert {:>12.0} ops/s, contains {:>12.0} ops/s",
+            throughput(insert),
+            throughput(contains)
+        );
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with cargo test --release -- --ignored"]
+    fn bench_hashers() {
+        let cap = 200_000;
+        let ints: Vec<u64> = (0..cap).map(|_| random()).collect();
+        let strs: Vec<String> = (0..cap)
+            .map(|_| {
+                thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(12)
+                    .map(char::from)
+                    .collect()
+            })
+            .collect();
+        println!("u64 keys");
+        bench_hasher("sip", BuildHasherDefault::<DefaultHasher>::default(), &ints);
+        bench_hasher("stable", StableBuildHasher::default(), &ints);
+        bench_hasher("fx", FxBuildHasher::default(), &ints);
+        println!("12-byte string keys");
+        bench_hasher("sip", BuildHasherDefault::<DefaultHasher>::default(), &strs);
+        bench_hasher("stable", StableBuildHasher::default(), &strs);
+        bench_hasher("fx", FxBuildHasher::default(), &strs);
+    }
+
     #[test]
     fn test_hash_set_insert_and_query() {
         let cap = 10_000_000;
@@ -0,0 +1,241 @@
+// 过滤器持久化用到的公共部分：小端整数读写和 CRC-32 校验。
+//
+// 文件格式统一为：魔数(4) | 版本(u16) | 具体内容 | CRC-32(u32)，校验和覆盖前面所有字节。
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::{Read, Write};
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-32 (IEEE 802.3).
+#[derive(Clone, Copy)]
+pub(crate) struct Crc32(u32);
+
+#[allow(unused)]
+impl Crc32 {
+    pub fn new() -> Self {
+        Crc32(!0)
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = CRC32_TABLE[((self.0 ^ byte as u32) & 0xff) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    pub fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Io(io::Error),
+    BadMagic([u8; 4]),
+    UnsupportedVersion(u16),
+    ChecksumMismatch { expected: u32, actual: u32 },
+    Corrupt(&'static str),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Io(err) => write!(f, "io error: {err}"),
+            DecodeError::BadMagic(magic) => write!(f, "bad magic bytes {magic:?}"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            DecodeError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {expected:#010x}, got {actual:#010x}"
+            ),
+            DecodeError::Corrupt(reason) => write!(f, "corrupt payload: {reason}"),
+        }
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecodeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(err: io::Error) -> Self {
+        DecodeError::Io(err)
+    }
+}
+
+/// Writes little-endian values while accumulating their checksum.
+pub(crate) struct Encoder<W: Write> {
+    writer: W,
+    crc: Crc32,
+}
+
+#[allow(unused)]
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W, magic: &[u8; 4], version: u16) -> io::Result<Self> {
+        let mut encoder = Encoder {
+            writer,
+            crc: Crc32::new(),
+        };
+        encoder.write_bytes(magic)?;
+        encoder.write_u16(version)?;
+        Ok(encoder)
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.crc.update(bytes);
+        self.writer.write_all(bytes)
+    }
+
+    pub fn write_u8(&mut self, value: u8) -> io::Result<()> {
+        self.write_bytes(&[value])
+    }
+
+    pub fn write_u16(&mut self, value: u16) -> io::Result<()> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_u32(&mut self, value: u32) -> io::Result<()> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_u64(&mut self, value: u64) -> io::Result<()> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    /// Appends the checksum of everything written so far.
+    pub fn finish(mut self) -> io::Result<()> {
+        let checksum = self.crc.finish();
+        self.writer.write_all(&checksum.to_le_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// Reads little-endian values while accumulating their checksum.
+pub(crate) struct Decoder<R: Read> {
+    reader: R,
+    crc: Crc32,
+}
+
+#[allow(unused)]
+impl<R: Read> Decoder<R> {
+    /// Checks the magic bytes and returns the decoder with the format version.
+    pub fn new(reader: R, magic: &[u8; 4]) -> Result<(Self, u16), DecodeError> {
+        let mut decoder = Decoder {
+            reader,
+            crc: Crc32::new(),
+        };
+        let actual = decoder.read_array::<4>()?;
+        if &actual != magic {
+            return Err(DecodeError::BadMagic(actual));
+        }
+        let version = decoder.read_u16()?;
+        Ok((decoder, version))
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes)?;
+        self.crc.update(&bytes);
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads the trailing checksum and compares it with the bytes read so far.
+    pub fn finish(mut self) -> Result<(), DecodeError> {
+        let actual = self.crc.finish();
+        let mut bytes = [0; 4];
+        self.reader.read_exact(&mut bytes)?;
+        let expected = u32::from_le_bytes(bytes);
+        if expected != actual {
+            return Err(DecodeError::ChecksumMismatch { expected, actual });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{Crc32, DecodeError, Decoder, Encoder};
+
+    #[test]
+    fn test_crc32() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut buf = Vec::new();
+        let mut encoder = Encoder::new(&mut buf, b"TEST", 3).unwrap();
+        encoder.write_u16(1).unwrap();
+        encoder.write_u32(2).unwrap();
+        encoder.write_u64(3).unwrap();
+        encoder.finish().unwrap();
+        assert_eq!(buf.len(), 4 + 2 + 2 + 4 + 8 + 4);
+
+        let (mut decoder, version) = Decoder::new(buf.as_slice(), b"TEST").unwrap();
+        assert_eq!(version, 3);
+        assert_eq!(decoder.read_u16().unwrap(), 1);
+        assert_eq!(decoder.read_u32().unwrap(), 2);
+        assert_eq!(decoder.read_u64().unwrap(), 3);
+        decoder.finish().unwrap();
+
+        buf[8] ^= 1;
+        let (mut decoder, _) = Decoder::new(buf.as_slice(), b"TEST").unwrap();
+        decoder.read_u16().unwrap();
+        decoder.read_u32().unwrap();
+        decoder.read_u64().unwrap();
+        assert!(matches!(
+            decoder.finish(),
+            Err(DecodeError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            Decoder::new(buf.as_slice(), b"ABCD"),
+            Err(DecodeError::BadMagic(magic)) if &magic == b"TEST"
+        ));
+    }
+}
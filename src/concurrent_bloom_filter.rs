@@ -0,0 +1,164 @@
+// 并发布隆过滤器：位数组换成 AtomicU64，插入用 fetch_or 置位，多个线程可以通过 &self
+// 同时插入和查询，不需要加锁。
+//
+// 置位只会把 0 变成 1，不会有线程把别人置的位清掉，所以 Relaxed 就足够了：一个插入完成后，
+// 任何与它建立了 happens-before 关系的查询（比如 join 之后）都能看到这些位。
+
+use crate::bloom_filter::{get_index, optimal_bits_size, optimal_hash_fn_count, DoubleHasher};
+use rand::random;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+const BLOCK_BITS: usize = u64::BITS as usize;
+
+#[allow(unused)]
+pub struct ConcurrentBloomFilter<T: ?Sized> {
+    bits: Vec<AtomicU64>,
+    bits_len: usize,
+    hash_fn_count: usize,
+    hasher: DoubleHasher,
+    len: AtomicUsize,
+    // 过滤器并不持有 T，只是对 &T 求哈希，所以无论 T 是什么都可以 Send + Sync
+    _phantom: PhantomData<fn(&T)>,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> ConcurrentBloomFilter<T> {
+    pub fn new(cap: usize, err_rate: f64) -> Self {
+        Self::with_seeds(cap, err_rate, [random(), random()])
+    }
+
+    pub fn with_seeds(cap: usize, err_rate: f64, seeds: [u64; 2]) -> Self {
+        let bits_size = optimal_bits_size(cap, err_rate);
+        let hash_fn_count = optimal_hash_fn_count(bits_size, cap);
+        ConcurrentBloomFilter {
+            bits: (0..bits_size.div_ceil(BLOCK_BITS))
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            bits_len: bits_size,
+            hash_fn_count,
+            hasher: DoubleHasher::with_seeds(seeds),
+            len: AtomicUsize::new(0),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn insert(&self, value: &T) {
+        let hash_tuple = self.hasher.cal_hash(value);
+        for fn_i in 0..self.hash_fn_count {
+            let index = get_index(hash_tuple, fn_i, self.bits_len);
+            self.bits[index / BLOCK_BITS].fetch_or(1 << (index % BLOCK_BITS), Ordering::Relaxed);
+        }
+        self.len.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let hash_tuple = self.hasher.cal_hash(value);
+        (0..self.hash_fn_count).all(|fn_i| {
+            let index = get_index(hash_tuple, fn_i, self.bits_len);
+            self.bits[index / BLOCK_BITS].load(Ordering::Relaxed) & (1 << (index % BLOCK_BITS)) != 0
+        })
+    }
+
+    /// Number of `insert` calls that have completed so far, duplicates included.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn bits_len(&self) -> usize {
+        self.bits_len
+    }
+
+    pub fn hash_fn_count(&self) -> usize {
+        self.hash_fn_count
+    }
+
+    pub fn seeds(&self) -> [u64; 2] {
+        self.hasher.seeds()
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|block| block.load(Ordering::Relaxed).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn fill_ratio(&self) -> f64 {
+        self.count_ones() as f64 / self.bits_len as f64
+    }
+
+    /// Expected false positive rate at the current fill level: (1 − e^(−kn/m))^k
+    pub fn expected_false_positive_rate(&self) -> f64 {
+        let k = self.hash_fn_count as f64;
+        let exponent = -k * self.len() as f64 / self.bits_len as f64;
+        (1f64 - exponent.exp()).powf(k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bloom_filter::BloomFilter;
+    use crate::concurrent_bloom_filter::ConcurrentBloomFilter;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<ConcurrentBloomFilter<str>>();
+        assert_send_sync::<ConcurrentBloomFilter<std::rc::Rc<u64>>>();
+    }
+
+    #[test]
+    fn test_concurrent_insert_no_false_negatives() {
+        let threads = 8;
+        let per_thread = 50_000u64;
+        let filter = ConcurrentBloomFilter::new(threads * per_thread as usize, 0.01);
+        thread::scope(|scope| {
+            for t in 0..threads as u64 {
+                let filter = &filter;
+                scope.spawn(move || {
+                    for key in t * per_thread..(t + 1) * per_thread {
+                        filter.insert(&key);
+                        assert!(filter.contains(&key));
+                    }
+                });
+            }
+            // 插入进行的同时也在查询：一旦查到存在，之后就不会再变回不存在
+            scope.spawn(|| {
+                let seen: Vec<u64> = (0..per_thread).filter(|key| filter.contains(key)).collect();
+                assert!(seen.iter().all(|key| filter.contains(key)));
+            });
+        });
+        assert_eq!(filter.len(), threads * per_thread as usize);
+        assert!((0..threads as u64 * per_thread).all(|key| filter.contains(&key)));
+    }
+
+    #[test]
+    fn test_same_bits_as_bloom_filter() {
+        let seeds = [3, 5];
+        let concurrent = ConcurrentBloomFilter::with_seeds(10_000, 0.01, seeds);
+        let mut filter = BloomFilter::with_seeds(10_000, 0.01, seeds);
+        thread::scope(|scope| {
+            for t in 0..4u64 {
+                let concurrent = &concurrent;
+                scope.spawn(move || {
+                    for key in (t..10_000).step_by(4) {
+                        concurrent.insert(&key);
+                    }
+                });
+            }
+        });
+        for key in 0..10_000u64 {
+            filter.insert(&key);
+        }
+        assert_eq!(concurrent.count_ones(), filter.count_ones());
+        assert!((10_000..20_000u64).all(|key| concurrent.contains(&key) == filter.contains(&key)));
+    }
+}
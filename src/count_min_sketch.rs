@@ -0,0 +1,263 @@
+// Count-Min Sketch（Cormode & Muthukrishnan）：估计数据流里每个元素出现的次数。
+//
+// d 行 w 列计数器，第 i 行用布隆过滤器同样的双重哈希 gi(x) = h1(x) + i·h2(x) mod w 选列。
+// 取 w = ⌈e / ε⌉，d = ⌈ln(1 / δ)⌉，则估计值不小于真实值，且以 1 − δ 的概率不超过真实值 + εN，
+// N 为所有计数之和。
+//
+// 保守更新（conservative update）只把各行计数器抬到 min + count，误差更小，但两个保守更新的
+// sketch 合并后只保证不低估。
+
+use crate::bloom_filter::{get_index, DoubleHasher};
+use rand::random;
+use std::error::Error;
+use std::f64::consts::E;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+#[allow(unused)]
+pub struct CountMinSketch<T: ?Sized> {
+    counters: Vec<u64>,
+    width: usize,
+    depth: usize,
+    hasher: DoubleHasher,
+    total: u64,
+    _phantom: PhantomData<T>,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> CountMinSketch<T> {
+    /// Estimates exceed the true count by at most `epsilon · total()` with
+    /// probability `1 − delta`.
+    pub fn new(epsilon: f64, delta: f64) -> Self {
+        Self::with_seeds(epsilon, delta, [random(), random()])
+    }
+
+    /// Same as `new`, but with fixed hash seeds so that sketches built on
+    /// different shards can be merged.
+    pub fn with_seeds(epsilon: f64, delta: f64, seeds: [u64; 2]) -> Self {
+        assert!(
+            epsilon > 0f64 && epsilon < 1f64,
+            "epsilon must be in (0, 1), got {epsilon}"
+        );
+        assert!(
+            delta > 0f64 && delta < 1f64,
+            "delta must be in (0, 1), got {delta}"
+        );
+        let width = (E / epsilon).ceil() as usize;
+        let depth = (1f64 / delta).ln().ceil().max(1f64) as usize;
+        Self::with_dimensions(width, depth, seeds)
+    }
+
+    pub fn with_dimensions(width: usize, depth: usize, seeds: [u64; 2]) -> Self {
+        assert!(width > 0 && depth > 0, "width and depth must be positive");
+        CountMinSketch {
+            counters: vec![0; width * depth],
+            width,
+            depth,
+            hasher: DoubleHasher::with_seeds(seeds),
+            total: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn indexes(&self, value: &T) -> impl Iterator<Item = usize> {
+        let hash_tuple = self.hasher.cal_hash(value);
+        let width = self.width;
+        (0..self.depth).map(move |row| row * width + get_index(hash_tuple, row, width))
+    }
+
+    pub fn increment(&mut self, value: &T) {
+        self.add(value, 1);
+    }
+
+    pub fn add(&mut self, value: &T, count: u64) {
+        let indexes: Vec<usize> = self.indexes(value).collect();
+        for index in indexes {
+            self.counters[index] = self.counters[index].saturating_add(count);
+        }
+        self.total = self.total.saturating_add(count);
+    }
+
+    /// Like `add`, but only raises each counter as far as the new estimate needs.
+    pub fn add_conservative(&mut self, value: &T, count: u64) {
+        let indexes: Vec<usize> = self.indexes(value).collect();
+        let target = indexes
+            .iter()
+            .map(|&index| self.counters[index])
+            .min()
+            .unwrap_or(0)
+            .saturating_add(count);
+        for index in indexes {
+            self.counters[index] = self.counters[index].max(target);
+        }
+        self.total = self.total.saturating_add(count);
+    }
+
+    /// Never less than the true count.
+    pub fn estimate(&self, value: &T) -> u64 {
+        self.indexes(value)
+            .map(|index| self.counters[index])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Sum of all counts added.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn seeds(&self) -> [u64; 2] {
+        self.hasher.seeds()
+    }
+
+    /// Adds the counts of a sketch built from another shard of the stream.
+    pub fn merge(&mut self, other: &Self) -> Result<(), CountMinMergeError> {
+        if self.width != other.width {
+            return Err(CountMinMergeError::Width(self.width, other.width));
+        }
+        if self.depth != other.depth {
+            return Err(CountMinMergeError::Depth(self.depth, other.depth));
+        }
+        if self.seeds() != other.seeds() {
+            return Err(CountMinMergeError::Seeds);
+        }
+        for (counter, other_counter) in self.counters.iter_mut().zip(&other.counters) {
+            *counter = counter.saturating_add(*other_counter);
+        }
+        self.total = self.total.saturating_add(other.total);
+        Ok(())
+    }
+}
+
+/// Merging needs both sketches to map every value to the same counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountMinMergeError {
+    Width(usize, usize),
+    Depth(usize, usize),
+    Seeds,
+}
+
+impl Display for CountMinMergeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountMinMergeError::Width(left, right) => {
+                write!(f, "sketch widths differ: {left} vs {right}")
+            }
+            CountMinMergeError::Depth(left, right) => {
+                write!(f, "sketch depths differ: {left} vs {right}")
+            }
+            CountMinMergeError::Seeds => write!(f, "hash seeds differ"),
+        }
+    }
+}
+
+impl Error for CountMinMergeError {}
+
+#[cfg(test)]
+mod test {
+    use crate::count_min_sketch::{CountMinMergeError, CountMinSketch};
+    use rand::{thread_rng, Rng};
+    use std::collections::HashMap;
+
+    /// 偏斜的数据流：key 越小出现得越多
+    fn skewed_stream(len: usize) -> Vec<u64> {
+        let mut rng = thread_rng();
+        (0..len)
+            .map(|_| {
+                let x: f64 = rng.gen();
+                (1f64 / (1f64 - x)).powf(1.5) as u64
+            })
+            .collect()
+    }
+
+    fn true_counts(stream: &[u64]) -> HashMap<u64, u64> {
+        let mut counts = HashMap::new();
+        for key in stream {
+            *counts.entry(*key).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let sketch = CountMinSketch::<u64>::new(0.001, 0.01);
+        assert_eq!(sketch.width(), 2719);
+        assert_eq!(sketch.depth(), 5);
+    }
+
+    #[test]
+    fn test_error_bound() {
+        let (epsilon, delta) = (0.001, 0.01);
+        let stream = skewed_stream(500_000);
+        let counts = true_counts(&stream);
+        let mut standard = CountMinSketch::with_seeds(epsilon, delta, [1, 2]);
+        let mut conservative = CountMinSketch::with_seeds(epsilon, delta, [1, 2]);
+        for key in &stream {
+            standard.increment(key);
+            conservative.add_conservative(key, 1);
+        }
+        assert_eq!(standard.total(), stream.len() as u64);
+
+        let bound = (epsilon * stream.len() as f64) as u64;
+        let mut over_bound = 0;
+        let (mut standard_error, mut conservative_error) = (0, 0);
+        for (key, &count) in &counts {
+            let estimate = standard.estimate(key);
+            let conservative_estimate = conservative.estimate(key);
+            assert!(estimate >= count);
+            assert!(conservative_estimate >= count);
+            assert!(conservative_estimate <= estimate);
+            if estimate - count > bound {
+                over_bound += 1;
+            }
+            standard_error += estimate - count;
+            conservative_error += conservative_estimate - count;
+        }
+        assert!(
+            (over_bound as f64) <= delta * counts.len() as f64,
+            "{over_bound} of {} distinct keys over the bound {bound}",
+            counts.len()
+        );
+        assert!(
+            conservative_error <= standard_error,
+            "total error: standard {standard_error}, conservative {conservative_error}"
+        );
+    }
+
+    #[test]
+    fn test_merge_shards() {
+        let stream = skewed_stream(100_000);
+        let seeds = [3, 4];
+        let mut whole = CountMinSketch::with_seeds(0.001, 0.01, seeds);
+        let mut shards: Vec<_> = (0..4)
+            .map(|_| CountMinSketch::with_seeds(0.001, 0.01, seeds))
+            .collect();
+        for (i, key) in stream.iter().enumerate() {
+            whole.increment(key);
+            shards[i % 4].increment(key);
+        }
+        let mut merged = shards.pop().unwrap();
+        for shard in &shards {
+            merged.merge(shard).unwrap();
+        }
+        assert_eq!(merged.total(), whole.total());
+        assert_eq!(merged.counters, whole.counters);
+
+        let other = CountMinSketch::with_seeds(0.001, 0.01, [5, 6]);
+        assert_eq!(merged.merge(&other), Err(CountMinMergeError::Seeds));
+        let narrow = CountMinSketch::with_seeds(0.01, 0.01, seeds);
+        assert_eq!(
+            merged.merge(&narrow),
+            Err(CountMinMergeError::Width(2719, 272))
+        );
+    }
+}
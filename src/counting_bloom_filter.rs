@@ -0,0 +1,201 @@
+// 计数布隆过滤器：把每个位换成一个 4 bit 的计数器，插入时加一，删除时减一，从而支持删除。
+//
+// 计数器达到上限 15 之后不再变化（饱和），删除时也不会减少，否则可能把别的元素的位置
+// 减到 0，造成误判为不存在。位置计算和容量参数与 BloomFilter 完全相同。
+
+use crate::bloom_filter::{get_index, optimal_bits_size, optimal_hash_fn_count, DoubleHasher};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+const COUNTER_BITS: usize = 4;
+const COUNTERS_PER_BYTE: usize = u8::BITS as usize / COUNTER_BITS;
+const COUNTER_MAX: u8 = (1 << COUNTER_BITS) - 1;
+
+#[allow(unused)]
+pub struct CountingBloomFilter<T: ?Sized> {
+    counters: Vec<u8>,
+    counters_len: usize,
+    hash_fn_count: usize,
+    hasher: DoubleHasher,
+    len: usize,
+    overflowed: bool,
+    _phantom: PhantomData<T>,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> CountingBloomFilter<T> {
+    pub fn new(cap: usize, err_rate: f64) -> Self {
+        let counters_size = optimal_bits_size(cap, err_rate);
+        let hash_fn_count = optimal_hash_fn_count(counters_size, cap);
+        CountingBloomFilter {
+            counters: vec![0; counters_size.div_ceil(COUNTERS_PER_BYTE)],
+            counters_len: counters_size,
+            hash_fn_count,
+            hasher: DoubleHasher::new(),
+            len: 0,
+            overflowed: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn indexes(&self, value: &T) -> impl Iterator<Item = usize> {
+        let hash_tuple = self.hasher.cal_hash(value);
+        let len = self.counters_len;
+        (0..self.hash_fn_count).map(move |fn_i| get_index(hash_tuple, fn_i, len))
+    }
+
+    fn get_counter(&self, index: usize) -> u8 {
+        let shift = (index % COUNTERS_PER_BYTE) * COUNTER_BITS;
+        (self.counters[index / COUNTERS_PER_BYTE] >> shift) & COUNTER_MAX
+    }
+
+    fn set_counter(&mut self, index: usize, counter: u8) {
+        let shift = (index % COUNTERS_PER_BYTE) * COUNTER_BITS;
+        let byte = &mut self.counters[index / COUNTERS_PER_BYTE];
+        *byte = (*byte & !(COUNTER_MAX << shift)) | (counter << shift);
+    }
+
+    pub fn insert(&mut self, value: &T) {
+        let indexes: Vec<usize> = self.indexes(value).collect();
+        for index in indexes {
+            match self.get_counter(index) {
+                COUNTER_MAX => self.overflowed = true,
+                counter => self.set_counter(index, counter + 1),
+            }
+        }
+        self.len += 1;
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.indexes(value).all(|index| self.get_counter(index) > 0)
+    }
+
+    /// Removes one occurrence of `value`, returning `false` if it is definitely absent.
+    ///
+    /// Removing a value that was never inserted but happens to be a false positive
+    /// decrements other values' counters and may cause false negatives.
+    pub fn remove(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        let indexes: Vec<usize> = self.indexes(value).collect();
+        for index in indexes {
+            match self.get_counter(index) {
+                COUNTER_MAX => {}
+                counter => self.set_counter(index, counter - 1),
+            }
+        }
+        self.len = self.len.saturating_sub(1);
+        true
+    }
+
+    /// Upper bound on how many times `value` has been inserted, unless a counter
+    /// it maps to has saturated.
+    pub fn count_estimate(&self, value: &T) -> usize {
+        self.indexes(value)
+            .map(|index| self.get_counter(index))
+            .min()
+            .unwrap_or(0) as usize
+    }
+
+    /// Whether any counter has ever been incremented past its maximum.
+    pub fn has_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Number of counters currently stuck at their maximum.
+    pub fn saturated_count(&self) -> usize {
+        (0..self.counters_len)
+            .filter(|&index| self.get_counter(index) == COUNTER_MAX)
+            .count()
+    }
+
+    /// Net number of values inserted minus values removed.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn counters_len(&self) -> usize {
+        self.counters_len
+    }
+
+    pub fn hash_fn_count(&self) -> usize {
+        self.hash_fn_count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::counting_bloom_filter::{CountingBloomFilter, COUNTER_MAX};
+
+    #[test]
+    fn test_insert_and_remove() {
+        let cap = 100_000;
+        let mut filter = CountingBloomFilter::new(cap, 0.01);
+        for key in 0..cap as u64 {
+            filter.insert(&key);
+        }
+        assert_eq!(filter.len(), cap);
+        assert!((0..cap as u64).all(|key| filter.contains(&key)));
+
+        for key in (0..cap as u64).step_by(2) {
+            assert!(filter.remove(&key));
+        }
+        assert_eq!(filter.len(), cap / 2);
+        // 没有删除的元素一定还在
+        assert!((1..cap as u64).step_by(2).all(|key| filter.contains(&key)));
+        let still_present = (0..cap as u64)
+            .step_by(2)
+            .filter(|key| filter.contains(key))
+            .count();
+        assert!((still_present as f64) < cap as f64 / 2f64 * 0.01 * 2f64);
+        assert!(!filter.has_overflowed());
+    }
+
+    #[test]
+    fn test_remove_absent() {
+        let mut filter = CountingBloomFilter::<str>::new(1_000, 0.01);
+        assert!(!filter.remove("absent"));
+        filter.insert("present");
+        assert!(filter.remove("present"));
+        assert!(!filter.contains("present"));
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_count_estimate() {
+        let mut filter = CountingBloomFilter::<str>::new(1_000, 0.01);
+        assert_eq!(filter.count_estimate("a"), 0);
+        for _ in 0..3 {
+            filter.insert("a");
+        }
+        filter.insert("b");
+        assert!(filter.count_estimate("a") >= 3);
+        assert!(filter.count_estimate("b") >= 1);
+        filter.remove("a");
+        assert!(filter.count_estimate("a") >= 2);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut filter = CountingBloomFilter::<str>::new(1_000, 0.01);
+        for _ in 0..COUNTER_MAX {
+            filter.insert("hot");
+        }
+        assert!(!filter.has_overflowed());
+        filter.insert("hot");
+        assert!(filter.has_overflowed());
+        assert!(filter.saturated_count() > 0);
+        assert_eq!(filter.count_estimate("hot"), COUNTER_MAX as usize);
+
+        // 饱和的计数器不再减少，不会产生假阴性
+        for _ in 0..=COUNTER_MAX {
+            filter.remove("hot");
+        }
+        assert!(filter.contains("hot"));
+    }
+}
@@ -0,0 +1,384 @@
+// 布谷鸟过滤器（Fan 等，Cuckoo Filter: Practically Better Than Bloom）。
+//
+// 每个元素只存一个 f 位的指纹，放在两个候选桶之一：
+// i1 = h1(x)，i2 = i1 ⊕ hash(fingerprint)
+// 因为 i1 = i2 ⊕ hash(fingerprint)，所以只凭桶里的指纹就能算出另一个候选桶，踢出元素时不需要原值。
+// 两个桶都满时随机踢出一个指纹挪到它的另一个桶，最多踢 MAX_KICKS 次，仍失败就把最后无处安放的
+// 指纹放进 victim，之后的插入直接报满，保证已经插入的元素不会丢。
+//
+// 误差率上限约为 2b / 2^f，所以 f = ⌈log2(2b / ε)⌉。
+
+use crate::bloom_filter::DoubleHasher;
+use crate::membership::ApproximateMembership;
+use rand::{random, thread_rng, Rng};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+const DEFAULT_BUCKET_SIZE: usize = 4;
+const MAX_FINGERPRINT_BITS: u32 = 16;
+const MAX_KICKS: usize = 500;
+const MAX_LOAD_FACTOR: f64 = 0.95;
+const WORD_BITS: usize = u64::BITS as usize;
+
+#[allow(unused)]
+pub struct CuckooFilter<T: ?Sized> {
+    // 指纹按 fingerprint_bits 位紧密排列，0 表示空槽
+    slots: Vec<u64>,
+    num_buckets: usize,
+    bucket_size: usize,
+    fingerprint_bits: u32,
+    hasher: DoubleHasher,
+    len: usize,
+    victim: Option<(usize, u16)>,
+    _phantom: PhantomData<T>,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> CuckooFilter<T> {
+    pub fn new(cap: usize, err_rate: f64) -> Self {
+        assert!(
+            err_rate > 0f64 && err_rate < 1f64,
+            "err_rate must be in (0, 1), got {err_rate}"
+        );
+        let fingerprint_bits = (2f64 * DEFAULT_BUCKET_SIZE as f64 / err_rate).log2().ceil() as u32;
+        Self::with_params(
+            cap,
+            fingerprint_bits.clamp(1, MAX_FINGERPRINT_BITS),
+            DEFAULT_BUCKET_SIZE,
+        )
+    }
+
+    pub fn with_params(cap: usize, fingerprint_bits: u32, bucket_size: usize) -> Self {
+        assert!(
+            (1..=MAX_FINGERPRINT_BITS).contains(&fingerprint_bits),
+            "fingerprint_bits must be in 1..={MAX_FINGERPRINT_BITS}, got {fingerprint_bits}"
+        );
+        assert!(bucket_size > 0, "bucket_size must be positive");
+        // 两个候选桶用异或互相计算，桶数必须是 2 的幂
+        let num_buckets = ((cap.max(1) as f64 / MAX_LOAD_FACTOR / bucket_size as f64).ceil()
+            as usize)
+            .next_power_of_two();
+        let slots_bits = num_buckets * bucket_size * fingerprint_bits as usize;
+        CuckooFilter {
+            slots: vec![0; slots_bits.div_ceil(WORD_BITS) + 1],
+            num_buckets,
+            bucket_size,
+            fingerprint_bits,
+            hasher: DoubleHasher::with_seeds([random(), random()]),
+            len: 0,
+            victim: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn fingerprint_mask(&self) -> u64 {
+        (1 << self.fingerprint_bits) - 1
+    }
+
+    fn get_slot(&self, slot: usize) -> u16 {
+        let bit = slot * self.fingerprint_bits as usize;
+        let (word, offset) = (bit / WORD_BITS, bit % WORD_BITS);
+        let mut value = self.slots[word] >> offset;
+        if offset + self.fingerprint_bits as usize > WORD_BITS {
+            value |= self.slots[word + 1] << (WORD_BITS - offset);
+        }
+        (value & self.fingerprint_mask()) as u16
+    }
+
+    fn set_slot(&mut self, slot: usize, fingerprint: u16) {
+        let mask = self.fingerprint_mask();
+        let bit = slot * self.fingerprint_bits as usize;
+        let (word, offset) = (bit / WORD_BITS, bit % WORD_BITS);
+        let value = fingerprint as u64 & mask;
+        self.slots[word] = (self.slots[word] & !(mask << offset)) | (value << offset);
+        if offset + self.fingerprint_bits as usize > WORD_BITS {
+            let shift = WORD_BITS - offset;
+            self.slots[word + 1] = (self.slots[word + 1] & !(mask >> shift)) | (value >> shift);
+        }
+    }
+
+    fn bucket_slots(&self, bucket: usize) -> std::ops::Range<usize> {
+        bucket * self.bucket_size..(bucket + 1) * self.bucket_size
+    }
+
+    fn index_and_fingerprint(&self, value: &T) -> (usize, u16) {
+        let (hash1, hash2) = self.hasher.cal_hash(value);
+        let index = hash1 as usize & (self.num_buckets - 1);
+        // 0 留给空槽
+        let fingerprint = (hash2 & self.fingerprint_mask()).max(1) as u16;
+        (index, fingerprint)
+    }
+
+    fn alt_index(&self, index: usize, fingerprint: u16) -> usize {
+        let hash = (fingerprint as u64).wrapping_mul(0x5bd1_e995_9e37_79b9);
+        (index ^ (hash >> 32) as usize) & (self.num_buckets - 1)
+    }
+
+    fn bucket_contains(&self, bucket: usize, fingerprint: u16) -> bool {
+        self.bucket_slots(bucket)
+            .any(|slot| self.get_slot(slot) == fingerprint)
+    }
+
+    fn try_put(&mut self, bucket: usize, fingerprint: u16) -> bool {
+        match self
+            .bucket_slots(bucket)
+            .find(|&slot| self.get_slot(slot) == 0)
+        {
+            Some(slot) => {
+                self.set_slot(slot, fingerprint);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn try_remove(&mut self, bucket: usize, fingerprint: u16) -> bool {
+        match self
+            .bucket_slots(bucket)
+            .find(|&slot| self.get_slot(slot) == fingerprint)
+        {
+            Some(slot) => {
+                self.set_slot(slot, 0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 放入指纹，放不下时返回最后被踢出、无处安放的指纹。
+    fn put_fingerprint(&mut self, index: usize, fingerprint: u16) -> Option<(usize, u16)> {
+        let alt_index = self.alt_index(index, fingerprint);
+        if self.try_put(index, fingerprint) || self.try_put(alt_index, fingerprint) {
+            return None;
+        }
+        let mut rng = thread_rng();
+        let mut index = if rng.gen() { index } else { alt_index };
+        let mut fingerprint = fingerprint;
+        for _ in 0..MAX_KICKS {
+            let slot = index * self.bucket_size + rng.gen_range(0..self.bucket_size);
+            let kicked = self.get_slot(slot);
+            self.set_slot(slot, fingerprint);
+            fingerprint = kicked;
+            index = self.alt_index(index, fingerprint);
+            if self.try_put(index, fingerprint) {
+                return None;
+            }
+        }
+        Some((index, fingerprint))
+    }
+
+    /// Inserts `value`, failing once the filter is full.
+    ///
+    /// The insert that exhausts the eviction loop still succeeds: the displaced
+    /// fingerprint is parked aside and every later insert reports `CuckooFull`
+    /// until something is removed.
+    pub fn insert(&mut self, value: &T) -> Result<(), CuckooFull> {
+        if self.victim.is_some() {
+            return Err(CuckooFull);
+        }
+        let (index, fingerprint) = self.index_and_fingerprint(value);
+        self.victim = self.put_fingerprint(index, fingerprint);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let (index, fingerprint) = self.index_and_fingerprint(value);
+        let alt_index = self.alt_index(index, fingerprint);
+        self.bucket_contains(index, fingerprint)
+            || self.bucket_contains(alt_index, fingerprint)
+            || self.victim.is_some_and(|(victim_index, victim)| {
+                victim == fingerprint && (victim_index == index || victim_index == alt_index)
+            })
+    }
+
+    /// Removes one occurrence of `value`, returning `false` if it was not found.
+    ///
+    /// Only remove values that were inserted, otherwise another value sharing the
+    /// same fingerprint may be removed instead.
+    pub fn remove(&mut self, value: &T) -> bool {
+        let (index, fingerprint) = self.index_and_fingerprint(value);
+        let alt_index = self.alt_index(index, fingerprint);
+        let removed = if self.victim.is_some_and(|(victim_index, victim)| {
+            victim == fingerprint && (victim_index == index || victim_index == alt_index)
+        }) {
+            self.victim = None;
+            true
+        } else {
+            self.try_remove(index, fingerprint) || self.try_remove(alt_index, fingerprint)
+        };
+        if !removed {
+            return false;
+        }
+        self.len -= 1;
+        // 腾出了位置，尝试把暂存的指纹放回去
+        if let Some((victim_index, victim)) = self.victim.take() {
+            self.victim = self.put_fingerprint(victim_index, victim);
+        }
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the last insert could not place every fingerprint.
+    pub fn is_full(&self) -> bool {
+        self.victim.is_some()
+    }
+
+    /// Total number of fingerprint slots.
+    pub fn capacity(&self) -> usize {
+        self.num_buckets * self.bucket_size
+    }
+
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    pub fn fingerprint_bits(&self) -> u32 {
+        self.fingerprint_bits
+    }
+
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.slots.len() * size_of::<u64>()
+    }
+
+    /// Upper bound on the false positive rate at the current load: 1 − (1 − 1/2^f)^(2bα)
+    pub fn false_positive_rate_bound(&self) -> f64 {
+        let compared = 2f64 * self.bucket_size as f64 * self.load_factor();
+        1f64 - (1f64 - 1f64 / (1u64 << self.fingerprint_bits) as f64).powf(compared)
+    }
+}
+
+impl<T: ?Sized + Hash> ApproximateMembership<T> for CuckooFilter<T> {
+    fn new(cap: usize, err_rate: f64) -> Self {
+        CuckooFilter::new(cap, err_rate)
+    }
+
+    fn insert(&mut self, value: &T) -> bool {
+        CuckooFilter::insert(self, value).is_ok()
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        CuckooFilter::contains(self, value)
+    }
+}
+
+/// The eviction loop gave up; remove some values before inserting more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CuckooFull;
+
+impl Display for CuckooFull {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cuckoo filter is full")
+    }
+}
+
+impl Error for CuckooFull {}
+
+#[cfg(test)]
+mod test {
+    use crate::bloom_filter::BloomFilter;
+    use crate::cuckoo_filter::{CuckooFilter, CuckooFull};
+    use crate::membership::ApproximateMembership;
+    use rand::random;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let cap = 100_000;
+        let mut filter = CuckooFilter::new(cap, 0.01);
+        assert_eq!(filter.fingerprint_bits(), 10);
+        for key in 0..cap as u64 {
+            filter.insert(&key).unwrap();
+        }
+        assert_eq!(filter.len(), cap);
+        assert!((0..cap as u64).all(|key| filter.contains(&key)));
+
+        for key in (0..cap as u64).step_by(2) {
+            assert!(filter.remove(&key));
+        }
+        assert_eq!(filter.len(), cap / 2);
+        assert!((1..cap as u64).step_by(2).all(|key| filter.contains(&key)));
+        let still_present = (0..cap as u64)
+            .step_by(2)
+            .filter(|key| filter.contains(key))
+            .count();
+        assert!(still_present < cap / 2 / 100);
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let cap = 200_000;
+        let err_rate = 0.01;
+        let mut filter = CuckooFilter::new(cap, err_rate);
+        let mut inserted = HashSet::with_capacity(cap);
+        while inserted.len() < cap {
+            let key: u64 = random();
+            if inserted.insert(key) {
+                filter.insert(&key).unwrap();
+            }
+        }
+        let probes = 200_000;
+        let false_positives = (0..probes)
+            .map(|_| random::<u64>())
+            .filter(|key| !inserted.contains(key) && filter.contains(key))
+            .count();
+        let measured = false_positives as f64 / probes as f64;
+        let load = filter.load_factor();
+        let bound = filter.false_positive_rate_bound();
+        assert!(
+            measured < err_rate,
+            "measured fpr {measured} at load {load}, err rate {err_rate}"
+        );
+        assert!(
+            measured < bound * 1.2,
+            "measured fpr {measured} at load {load}, bound {bound}"
+        );
+    }
+
+    #[test]
+    fn test_reports_full() {
+        for (fingerprint_bits, bucket_size) in [(4, 1), (7, 2), (12, 4), (16, 8)] {
+            let mut filter = CuckooFilter::with_params(1_000, fingerprint_bits, bucket_size);
+            let mut inserted = Vec::new();
+            let mut key = 0u64;
+            while filter.insert(&key).is_ok() {
+                inserted.push(key);
+                key += 1;
+                assert!(key <= filter.capacity() as u64 + 1);
+            }
+            assert_eq!(filter.insert(&key), Err(CuckooFull));
+            assert!(filter.is_full());
+            // 报满之前插入的元素一个都不能丢
+            assert!(inserted.iter().all(|key| filter.contains(key)));
+            assert!(filter.remove(&inserted[0]));
+            assert!(inserted[1..].iter().all(|key| filter.contains(key)));
+        }
+    }
+
+    #[test]
+    fn test_swappable_with_bloom_filter() {
+        fn count_hits<F: ApproximateMembership<str>>(words: &[String]) -> usize {
+            let mut filter = F::new(words.len(), 0.01);
+            for word in words {
+                assert!(filter.insert(word));
+            }
+            words.iter().filter(|word| filter.contains(word)).count()
+        }
+        let words: Vec<String> = (0..10_000).map(|i| format!("word-{i}")).collect();
+        assert_eq!(count_hits::<BloomFilter<str>>(&words), words.len());
+        assert_eq!(count_hits::<CuckooFilter<str>>(&words), words.len());
+    }
+}
@@ -0,0 +1,497 @@
+// d 叉堆：每个节点有 D 个子节点，树高为 log_D(n)。
+//
+// 上浮只和父节点比较，每层 1 次比较，比二叉堆少走 log2(D) 倍的层数；下沉每层要在 D 个
+// 子节点里选出最靠前的一个，需要 D 次比较，但 D 个子节点在内存中是连续的，常落在同一条
+// 缓存行里。push / decrease-key 多、pop 少的场景（比如 Dijkstra）通常 D = 4 更快。
+//
+// BinaryHeap 就是 D = 2 的 DaryHeap。上浮、下沉和建堆都用 binary_heap 中按叉数参数化的
+// sift 函数，位置从 1 开始编号，位置 i 的元素存放在 data[i - 1]。
+
+use crate::binary_heap::{
+    heapify, sift_down, sift_up, Compare, FnOrder, KeyOrder, MaxOrder, MinOrder,
+};
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
+
+#[allow(unused)]
+pub struct DaryHeap<T, const D: usize, C = MinOrder> {
+    data: Vec<T>,
+    cmp: C,
+}
+
+#[allow(unused)]
+impl<T: Ord, const D: usize> DaryHeap<T, D> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_comparator(capacity, MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T: Ord, const D: usize> DaryHeap<T, D, MaxOrder> {
+    pub fn new_max() -> Self {
+        Self::with_comparator(MaxOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T, F: Fn(&T, &T) -> Ordering, const D: usize> DaryHeap<T, D, FnOrder<F>> {
+    /// Heap ordered by `cmp`, where `Less` means the first argument pops first.
+    pub fn by(cmp: F) -> Self {
+        Self::with_comparator(FnOrder(cmp))
+    }
+}
+
+#[allow(unused)]
+impl<T, K: Ord, F: Fn(&T) -> K, const D: usize> DaryHeap<T, D, KeyOrder<F>> {
+    /// Heap that pops the element with the smallest `key` first.
+    pub fn by_key(key: F) -> Self {
+        Self::with_comparator(KeyOrder(key))
+    }
+}
+
+#[allow(unused)]
+impl<T, C: Compare<T>, const D: usize> DaryHeap<T, D, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::with_capacity_and_comparator(0, cmp)
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        const { assert!(D >= 2, "arity D must be at least 2") };
+        DaryHeap {
+            data: Vec::with_capacity(capacity),
+            cmp,
+        }
+    }
+
+    /// Builds a heap from `data` in O(n) by bottom-up heapify.
+    pub fn from_vec_with_comparator(mut data: Vec<T>, cmp: C) -> Self {
+        const { assert!(D >= 2, "arity D must be at least 2") };
+        heapify::<D, _, _>(&mut data, &cmp);
+        DaryHeap { data, cmp }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+
+    pub fn push(&mut self, val: T) {
+        self.data.push(val);
+        let size = self.data.len();
+        sift_up::<D, _, _>(&mut self.data, &self.cmp, size);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match self.len() {
+            0 => None,
+            1 => self.data.pop(),
+            size => {
+                self.data.swap(0, size - 1);
+                let data = self.data.pop();
+                sift_down::<D, _, _>(&mut self.data, &self.cmp, 1);
+                data
+            }
+        }
+    }
+
+    /// The element `pop` would return next.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Mutable access to the top element; the heap is restored when the guard is dropped.
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, D, C>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(PeekMut { heap: self })
+        }
+    }
+
+    /// Consumes the heap and returns its elements in the order `pop` would return them.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // 原地排序后最先出堆的元素在末尾，反转一次即为出堆顺序
+        for end in (1..self.data.len()).rev() {
+            self.data.swap(0, end);
+            sift_down::<D, _, _>(&mut self.data[..end], &self.cmp, 1);
+        }
+        self.data.reverse();
+        self.data
+    }
+
+    /// Iterates over the elements in arbitrary (storage) order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Removes all elements, yielding them in arbitrary order.
+    pub fn drain(&mut self) -> std::vec::Drain<'_, T> {
+        self.data.drain(..)
+    }
+
+    /// Removes all elements, yielding them in the order `pop` would return them.
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T, D, C> {
+        DrainSorted { heap: self }
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, then rebuilds the heap in O(n).
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        let len = self.data.len();
+        self.data.retain(f);
+        if self.data.len() < len {
+            heapify::<D, _, _>(&mut self.data, &self.cmp);
+        }
+    }
+
+    /// Moves all elements of `other` into `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        let start = self.data.len();
+        self.data.append(&mut other.data);
+        self.rebuild_tail(start);
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+
+    // data[start..] 是新追加的元素。逐个上浮的代价约为 k·log_D(n)，整体重建约为 2n，
+    // 取较小的一个，这样合并一个大堆也只需要 O(n + k)。
+    fn rebuild_tail(&mut self, start: usize) {
+        let len = self.data.len();
+        let tail_len = len - start;
+        if tail_len == 0 {
+            return;
+        }
+        let rebuild = start == 0 || 2 * len < tail_len * start.ilog(D) as usize;
+        if rebuild {
+            heapify::<D, _, _>(&mut self.data, &self.cmp);
+        } else {
+            for index in start + 1..=len {
+                sift_up::<D, _, _>(&mut self.data, &self.cmp, index);
+            }
+        }
+    }
+}
+
+/// Iterator returned by `DaryHeap::drain_sorted`. Elements not yet yielded are
+/// dropped together with the iterator.
+pub struct DrainSorted<'a, T, const D: usize, C: Compare<T>> {
+    heap: &'a mut DaryHeap<T, D, C>,
+}
+
+impl<T, const D: usize, C: Compare<T>> Iterator for DrainSorted<'_, T, D, C> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T, const D: usize, C: Compare<T>> ExactSizeIterator for DrainSorted<'_, T, D, C> {}
+
+impl<T, const D: usize, C: Compare<T>> Drop for DrainSorted<'_, T, D, C> {
+    fn drop(&mut self) {
+        self.heap.clear();
+    }
+}
+
+/// Guard returned by `DaryHeap::peek_mut`.
+pub struct PeekMut<'a, T, const D: usize, C: Compare<T>> {
+    heap: &'a mut DaryHeap<T, D, C>,
+}
+
+#[allow(unused)]
+impl<T, const D: usize, C: Compare<T>> PeekMut<'_, T, D, C> {
+    /// Removes the peeked element from the heap and returns it.
+    pub fn pop(this: Self) -> T {
+        let mut this = std::mem::ManuallyDrop::new(this);
+        this.heap
+            .pop()
+            .expect("PeekMut is only created for non-empty heaps")
+    }
+}
+
+impl<T, const D: usize, C: Compare<T>> Deref for PeekMut<'_, T, D, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.data[0]
+    }
+}
+
+impl<T, const D: usize, C: Compare<T>> DerefMut for PeekMut<'_, T, D, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.heap.data[0]
+    }
+}
+
+impl<T, const D: usize, C: Compare<T>> Drop for PeekMut<'_, T, D, C> {
+    fn drop(&mut self) {
+        // 堆顶可能被改大了，沉到合适的位置
+        let heap = &mut *self.heap;
+        sift_down::<D, _, _>(&mut heap.data, &heap.cmp, 1);
+    }
+}
+
+impl<T: Ord, const D: usize> Default for DaryHeap<T, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, const D: usize> From<Vec<T>> for DaryHeap<T, D> {
+    fn from(data: Vec<T>) -> Self {
+        Self::from_vec_with_comparator(data, MinOrder)
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> Extend<T> for DaryHeap<T, D, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let start = self.data.len();
+        self.data.extend(iter);
+        self.rebuild_tail(start);
+    }
+}
+
+impl<'a, T: Copy + 'a, C: Compare<T>, const D: usize> Extend<&'a T> for DaryHeap<T, D, C> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, C, const D: usize> IntoIterator for DaryHeap<T, D, C> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    /// Consumes the heap, yielding its elements in arbitrary order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T, C, const D: usize> IntoIterator for &'a DaryHeap<T, D, C> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<T: Ord, const D: usize> FromIterator<T> for DaryHeap<T, D> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary_heap::{heap_test_suite, MaxOrder, MinOrder};
+    use crate::dary_heap::{DaryHeap, PeekMut};
+    use rand::{thread_rng, Rng};
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap as StdBinaryHeap;
+    use std::time::Instant;
+
+    type QuaternaryHeap<T, C = MinOrder> = DaryHeap<T, 4, C>;
+
+    heap_test_suite!(QuaternaryHeap);
+
+    fn random_vec(len: usize, max: u32) -> Vec<u32> {
+        let mut rng = thread_rng();
+        (0..len).map(|_| rng.gen_range(0..max)).collect()
+    }
+
+    fn sorted(mut data: Vec<u32>) -> Vec<u32> {
+        data.sort();
+        data
+    }
+
+    fn check_matches_std<const D: usize>() {
+        let mut rng = thread_rng();
+        for len in [0, 1, 2, D, D + 1, 100, 1_000] {
+            let data = random_vec(len, 50);
+            let mut heap = DaryHeap::<_, D>::from(data.clone());
+            let mut std_heap: StdBinaryHeap<_> = data.iter().copied().map(Reverse).collect();
+            for _ in 0..2 * len {
+                if rng.gen_bool(0.5) {
+                    let val = rng.gen_range(0..50);
+                    heap.push(val);
+                    std_heap.push(Reverse(val));
+                } else {
+                    assert_eq!(heap.pop(), std_heap.pop().map(|val| val.0));
+                }
+                assert_eq!(heap.peek().copied(), std_heap.peek().map(|val| val.0));
+            }
+            assert_eq!(heap.len(), std_heap.len());
+            let expected: Vec<_> = std_heap
+                .into_sorted_vec()
+                .into_iter()
+                .rev()
+                .map(|val| val.0)
+                .collect();
+            assert_eq!(heap.into_sorted_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn test_matches_std() {
+        check_matches_std::<2>();
+        check_matches_std::<3>();
+        check_matches_std::<4>();
+        check_matches_std::<8>();
+    }
+
+    #[test]
+    fn test_orders() {
+        let mut heap = DaryHeap::<_, 4, _>::new_max();
+        heap.extend([2, 8, 1, 9, 5]);
+        assert_eq!(heap.drain_sorted().collect::<Vec<_>>(), [9, 8, 5, 2, 1]);
+
+        let words = ["pear", "fig", "banana", "kiwi", "apple"];
+        let mut by_len =
+            DaryHeap::<_, 3, _>::by(|a: &&str, b: &&str| b.len().cmp(&a.len()).then(a.cmp(b)));
+        by_len.extend(words);
+        assert_eq!(
+            by_len.drain_sorted().collect::<Vec<_>>(),
+            ["banana", "apple", "kiwi", "pear", "fig"]
+        );
+
+        let dist = [7, 3, 9, 1];
+        let mut by_key = DaryHeap::<_, 8, _>::by_key(|node: &usize| dist[*node]);
+        by_key.extend(0..dist.len());
+        assert_eq!(by_key.into_sorted_vec(), [3, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_peek_mut() {
+        let mut heap = DaryHeap::<_, 4>::from(vec![5, 3, 8, 1]);
+        if let Some(mut top) = heap.peek_mut() {
+            *top = 10;
+        }
+        assert_eq!(heap.peek(), Some(&3));
+        assert_eq!(PeekMut::pop(heap.peek_mut().unwrap()), 3);
+        assert_eq!(heap.into_sorted_vec(), [5, 8, 10]);
+    }
+
+    #[test]
+    fn test_bulk_operations() {
+        let data = random_vec(1_000, 100);
+        let heap: DaryHeap<_, 4> = data.iter().copied().collect();
+        assert_eq!(sorted(heap.iter().copied().collect()), sorted(data.clone()));
+        assert_eq!(
+            sorted((&heap).into_iter().copied().collect()),
+            sorted(data.clone())
+        );
+        assert_eq!(sorted(heap.into_iter().collect()), sorted(data.clone()));
+
+        let mut heap = DaryHeap::<_, 4>::from(data.clone());
+        heap.retain(|val| val % 3 == 0);
+        let expected: Vec<_> = data.iter().copied().filter(|val| val % 3 == 0).collect();
+        assert_eq!(heap.drain_sorted().collect::<Vec<_>>(), sorted(expected));
+
+        for (left, right) in [(0, 100), (1_000, 3), (3, 1_000)] {
+            let left = random_vec(left, 100);
+            let right = random_vec(right, 100);
+            let mut heap = DaryHeap::<_, 4, _>::from_vec_with_comparator(left.clone(), MaxOrder);
+            let mut other = DaryHeap::<_, 4, _>::from_vec_with_comparator(right.clone(), MaxOrder);
+            heap.append(&mut other);
+            assert!(other.is_empty());
+            let mut expected = sorted([left, right].concat());
+            expected.reverse();
+            assert_eq!(heap.into_sorted_vec(), expected);
+        }
+
+        let mut heap = DaryHeap::<_, 4>::from(data);
+        assert_eq!(heap.drain().count(), 1_000);
+        assert!(heap.is_empty());
+        heap.shrink_to_fit();
+        assert_eq!(heap.capacity(), 0);
+    }
+
+    fn bench_push_pop<const D: usize>(keys: &[u32]) -> u128 {
+        let start = Instant::now();
+        let mut heap = DaryHeap::<_, D>::with_capacity(keys.len());
+        for key in keys {
+            heap.push(*key);
+        }
+        let mut last = 0;
+        while let Some(key) = heap.pop() {
+            assert!(key >= last);
+            last = key;
+        }
+        start.elapsed().as_millis()
+    }
+
+    fn random_graph(nodes: usize, edges: usize) -> Vec<Vec<(usize, u64)>> {
+        let mut rng = thread_rng();
+        let mut graph: Vec<Vec<(usize, u64)>> = (0..nodes).map(|_| Vec::new()).collect();
+        for _ in 0..edges {
+            graph[rng.gen_range(0..nodes)].push((rng.gen_range(0..nodes), rng.gen_range(1..1_000)));
+        }
+        graph
+    }
+
+    // 懒删除版 Dijkstra：push 远多于有效的 pop，对应 decrease-key 多的场景
+    fn bench_dijkstra<const D: usize>(graph: &[Vec<(usize, u64)>]) -> (u128, u64) {
+        let start = Instant::now();
+        let mut dist = vec![u64::MAX; graph.len()];
+        let mut heap = DaryHeap::<(u64, usize), D>::new();
+        dist[0] = 0;
+        heap.push((0, 0));
+        while let Some((cost, node)) = heap.pop() {
+            if cost > dist[node] {
+                continue;
+            }
+            for &(next, weight) in &graph[node] {
+                if cost + weight < dist[next] {
+                    dist[next] = cost + weight;
+                    heap.push((cost + weight, next));
+                }
+            }
+        }
+        let checksum = dist.iter().filter(|cost| **cost != u64::MAX).sum();
+        (start.elapsed().as_millis(), checksum)
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with cargo test --release -- --ignored"]
+    fn bench_arity() {
+        // BinaryHeap 就是 D = 2
+        let keys = random_vec(500_000, u32::MAX);
+        println!("push/pop D = 2: {} ms", bench_push_pop::<2>(&keys));
+        println!("push/pop D = 4: {} ms", bench_push_pop::<4>(&keys));
+        println!("push/pop D = 8: {} ms", bench_push_pop::<8>(&keys));
+
+        let graph = random_graph(100_000, 1_000_000);
+        let (time_2, checksum_2) = bench_dijkstra::<2>(&graph);
+        let (time_4, checksum_4) = bench_dijkstra::<4>(&graph);
+        let (time_8, checksum_8) = bench_dijkstra::<8>(&graph);
+        assert_eq!(checksum_2, checksum_4);
+        assert_eq!(checksum_2, checksum_8);
+        println!("dijkstra D = 2: {time_2} ms, D = 4: {time_4} ms, D = 8: {time_8} ms");
+    }
+}
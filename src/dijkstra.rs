@@ -1,34 +1,17 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use crate::binary_heap::BinaryHeap;
+use crate::indexed_priority_queue::IndexedPriorityQueue;
+use crate::radix_heap::RadixHeap;
+use crate::stable_binary_heap::StableBinaryHeap;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+// States are ordered by cost alone; a stable heap pops states of equal cost in
+// the order they were pushed, so the search order is deterministic.
+#[derive(Copy, Clone)]
 struct State {
     cost: usize,
     position: usize,
 }
 
-// The priority queue depends on `Ord`.
-// Explicitly implement the trait so the queue becomes a min-heap
-// instead of a max-heap.
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Notice that the we flip the ordering on costs.
-        // In case of a tie we compare positions - this step is necessary
-        // to make implementations of `PartialEq` and `Ord` consistent.
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
-}
-
-// `PartialOrd` needs to be implemented as well.
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 // Each node is represented as a `usize`, for a shorter implementation.
 struct Edge {
     node: usize,
@@ -39,14 +22,14 @@ struct Edge {
 
 // Start at `start` and use `dist` to track the current shortest distance
 // to each node. This implementation isn't memory-efficient as it may leave duplicate
-// nodes in the queue. It also uses `usize::MAX` as a sentinel value,
-// for a simpler implementation.
+// nodes in the queue (see `shortest_path_indexed` for one that doesn't).
+// It also uses `usize::MAX` as a sentinel value, for a simpler implementation.
 #[allow(unused)]
-fn shortest_path(adj_list: &Vec<Vec<Edge>>, start: usize, goal: usize) -> Option<usize> {
+fn shortest_path(adj_list: &[Vec<Edge>], start: usize, goal: usize) -> Option<usize> {
     // dist[node] = current shortest distance from `start` to `node`
     let mut dist: Vec<_> = (0..adj_list.len()).map(|_| usize::MAX).collect();
 
-    let mut heap = BinaryHeap::new();
+    let mut heap = StableBinaryHeap::by_key(|state: &State| state.cost);
 
     // We're at `start`, with a zero cost
     dist[start] = 0;
@@ -88,6 +71,67 @@ fn shortest_path(adj_list: &Vec<Vec<Edge>>, start: usize, goal: usize) -> Option
     None
 }
 
+// Same as `shortest_path`, but each node is queued at most once: relaxing an edge
+// lowers the node's priority in place instead of pushing a duplicate, so the queue
+// never holds more than V entries and nothing popped is stale.
+#[allow(unused)]
+fn shortest_path_indexed(adj_list: &[Vec<Edge>], start: usize, goal: usize) -> Option<usize> {
+    let mut dist: Vec<_> = (0..adj_list.len()).map(|_| usize::MAX).collect();
+    let mut queue = IndexedPriorityQueue::with_capacity(adj_list.len());
+
+    dist[start] = 0;
+    queue.push_or_decrease(start, 0);
+
+    while let Some((position, cost)) = queue.pop() {
+        if position == goal {
+            return Some(cost);
+        }
+
+        for edge in &adj_list[position] {
+            let next_cost = cost + edge.cost;
+            if next_cost < dist[edge.node] {
+                dist[edge.node] = next_cost;
+                queue.push_or_decrease(edge.node, next_cost);
+            }
+        }
+    }
+
+    None
+}
+
+// Same as `shortest_path`, but on a radix heap. Every pushed cost is at least
+// the cost just popped, so the keys are monotone and the heap never compares
+// two states: it only buckets costs by their highest bit that differs from the
+// last popped one.
+#[allow(unused)]
+fn shortest_path_radix(adj_list: &[Vec<Edge>], start: usize, goal: usize) -> Option<usize> {
+    let mut dist: Vec<_> = (0..adj_list.len()).map(|_| usize::MAX).collect();
+    let mut heap = RadixHeap::new();
+
+    dist[start] = 0;
+    heap.push(0, start);
+
+    while let Some((cost, position)) = heap.pop() {
+        if position == goal {
+            return Some(cost);
+        }
+
+        if cost > dist[position] {
+            continue;
+        }
+
+        for edge in &adj_list[position] {
+            let next_cost = cost + edge.cost;
+            if next_cost < dist[edge.node] {
+                heap.push(next_cost, edge.node);
+                dist[edge.node] = next_cost;
+            }
+        }
+    }
+
+    None
+}
+
 #[derive(Hash, Copy, Clone, Eq, PartialEq, Debug)]
 struct Point<'t> {
     name: &'t str,
@@ -105,32 +149,13 @@ struct Visit<'t> {
     distance: usize,
 }
 
-impl<'a> Eq for Visit<'a> {}
-
-impl<'a> PartialEq<Self> for Visit<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.distance.eq(&other.distance)
-    }
-}
-
-impl<'a> PartialOrd<Self> for Visit<'a> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl<'a> Ord for Visit<'a> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.distance.cmp(&self.distance)
-    }
-}
-
 #[allow(unused)]
 fn shortest_path_new<'a>(
     graph: &HashMap<Point<'a>, Vec<(Point<'a>, usize)>>,
     start: Point<'a>,
 ) -> HashMap<Point<'a>, usize> {
-    let mut to_visit = BinaryHeap::new();
+    // Nearest point first
+    let mut to_visit = BinaryHeap::by_key(|visit: &Visit| visit.distance);
     let mut result = HashMap::new();
     let mut visited = HashSet::new();
     result.insert(start, 0);
@@ -146,7 +171,7 @@ fn shortest_path_new<'a>(
                     "start: {:?} -> to: {:?}, distance: {}; ",
                     start, neighbor, new_distance
                 );
-                let is_shorter = result.get(neighbor).map_or(true, |cur| new_distance < *cur);
+                let is_shorter = result.get(neighbor).is_none_or(|cur| new_distance < *cur);
                 if is_shorter {
                     result.insert(*neighbor, new_distance);
                     to_visit.push(Visit {
@@ -163,8 +188,13 @@ fn shortest_path_new<'a>(
 
 #[cfg(test)]
 mod test {
-    use crate::dijkstra::{shortest_path, shortest_path_new, Edge, Point};
-    use std::collections::HashMap;
+    use crate::dijkstra::{
+        shortest_path, shortest_path_indexed, shortest_path_new, shortest_path_radix, Edge, Point,
+    };
+    use rand::{thread_rng, Rng};
+    use std::cmp::Reverse;
+    use std::collections::{BinaryHeap, HashMap};
+    use std::time::Instant;
 
     #[test]
     fn test_table_graph() {
@@ -184,6 +214,11 @@ mod test {
         for (v, d) in &distances {
             println!("{} to {}, min distance: {d}", s.name, v.name);
         }
+        assert_eq!(distances[&s], 0);
+        assert_eq!(distances[&t], 8);
+        assert_eq!(distances[&x], 9);
+        assert_eq!(distances[&y], 5);
+        assert_eq!(distances[&z], 7);
     }
 
     #[test]
@@ -231,5 +266,107 @@ mod test {
         assert_eq!(shortest_path(&graph, 3, 0), Some(7));
         assert_eq!(shortest_path(&graph, 0, 4), Some(5));
         assert_eq!(shortest_path(&graph, 4, 0), None);
+
+        assert_eq!(shortest_path_indexed(&graph, 0, 1), Some(1));
+        assert_eq!(shortest_path_indexed(&graph, 0, 3), Some(3));
+        assert_eq!(shortest_path_indexed(&graph, 3, 0), Some(7));
+        assert_eq!(shortest_path_indexed(&graph, 0, 4), Some(5));
+        assert_eq!(shortest_path_indexed(&graph, 4, 0), None);
+
+        assert_eq!(shortest_path_radix(&graph, 0, 1), Some(1));
+        assert_eq!(shortest_path_radix(&graph, 0, 3), Some(3));
+        assert_eq!(shortest_path_radix(&graph, 3, 0), Some(7));
+        assert_eq!(shortest_path_radix(&graph, 0, 4), Some(5));
+        assert_eq!(shortest_path_radix(&graph, 4, 0), None);
+    }
+
+    fn random_graph(nodes: usize, edges: usize, max_cost: usize) -> Vec<Vec<Edge>> {
+        let mut rng = thread_rng();
+        let mut graph: Vec<Vec<Edge>> = (0..nodes).map(|_| Vec::new()).collect();
+        for _ in 0..edges {
+            graph[rng.gen_range(0..nodes)].push(Edge {
+                node: rng.gen_range(0..nodes),
+                cost: rng.gen_range(0..=max_cost),
+            });
+        }
+        graph
+    }
+
+    #[test]
+    fn test_indexed_matches_lazy_deletion() {
+        let mut rng = thread_rng();
+        let graph = random_graph(500, 3_000, 100);
+        for _ in 0..100 {
+            let start = rng.gen_range(0..graph.len());
+            let goal = rng.gen_range(0..graph.len());
+            assert_eq!(
+                shortest_path_indexed(&graph, start, goal),
+                shortest_path(&graph, start, goal)
+            );
+        }
+    }
+
+    #[test]
+    fn test_radix_matches_lazy_deletion() {
+        let mut rng = thread_rng();
+        // 包含代价为 0 的边，弹出的键会原地不动
+        let graph = random_graph(500, 3_000, 100);
+        for _ in 0..100 {
+            let start = rng.gen_range(0..graph.len());
+            let goal = rng.gen_range(0..graph.len());
+            assert_eq!(
+                shortest_path_radix(&graph, start, goal),
+                shortest_path(&graph, start, goal)
+            );
+        }
+    }
+
+    // 对照组：std 的 BinaryHeap 是大顶堆，用 Reverse 变成小顶堆
+    fn shortest_path_std(adj_list: &[Vec<Edge>], start: usize, goal: usize) -> Option<usize> {
+        let mut dist = vec![usize::MAX; adj_list.len()];
+        let mut heap = BinaryHeap::new();
+        dist[start] = 0;
+        heap.push(Reverse((0, start)));
+        while let Some(Reverse((cost, position))) = heap.pop() {
+            if position == goal {
+                return Some(cost);
+            }
+            if cost > dist[position] {
+                continue;
+            }
+            for edge in &adj_list[position] {
+                let next_cost = cost + edge.cost;
+                if next_cost < dist[edge.node] {
+                    heap.push(Reverse((next_cost, edge.node)));
+                    dist[edge.node] = next_cost;
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with cargo test --release -- --ignored"]
+    fn bench_radix_vs_std() {
+        let mut rng = thread_rng();
+        let graph = random_graph(200_000, 2_000_000, 10_000);
+        let queries: Vec<_> = (0..5)
+            .map(|_| (rng.gen_range(0..graph.len()), rng.gen_range(0..graph.len())))
+            .collect();
+
+        let start = Instant::now();
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|(from, to)| shortest_path_std(&graph, *from, *to))
+            .collect();
+        println!("std BinaryHeap: {} ms", start.elapsed().as_millis());
+
+        let start = Instant::now();
+        let actual: Vec<_> = queries
+            .iter()
+            .map(|(from, to)| shortest_path_radix(&graph, *from, *to))
+            .collect();
+        println!("RadixHeap: {} ms", start.elapsed().as_millis());
+        assert_eq!(actual, expected);
     }
 }
@@ -0,0 +1,304 @@
+// 与 Rust 版本、进程、平台都无关的哈希函数。
+//
+// std 的 DefaultHasher 不保证不同版本之间结果一致，持久化的过滤器换个进程读出来就对不上了。
+// StableHasher 固定为 SipHash-1-3，并把所有整数按小端序、usize 按 u64 写入。
+//
+// FxHasher 是 rustc 内部使用的非加密哈希，每个字一次乘法，短 key 比 SipHash 快很多，
+// 但结果同样与平台无关（按小端序读取），可以用来替换过滤器的默认哈希。
+// rustc 的 Fx 没有收尾步骤：乘法只会把低位的变化传到高位，只有高位不同的 key 算出的哈希
+// 只有高位不同；过滤器的 h1、h2 又只差一个种子，两者高度相关，h1 + i·h2 会退化。所以
+// finish 时再做一次 MurmurHash3 的 fmix64，让每一位都影响结果的每一位。
+
+use std::hash::{BuildHasherDefault, Hasher};
+
+#[allow(unused)]
+pub type StableBuildHasher = BuildHasherDefault<StableHasher>;
+#[allow(unused)]
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+#[allow(unused)]
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+macro_rules! sip_round {
+    ($v0: expr, $v1: expr, $v2: expr, $v3: expr) => {
+        $v0 = $v0.wrapping_add($v1);
+        $v1 = $v1.rotate_left(13);
+        $v1 ^= $v0;
+        $v0 = $v0.rotate_left(32);
+        $v2 = $v2.wrapping_add($v3);
+        $v3 = $v3.rotate_left(16);
+        $v3 ^= $v2;
+        $v0 = $v0.wrapping_add($v3);
+        $v3 = $v3.rotate_left(21);
+        $v3 ^= $v0;
+        $v2 = $v2.wrapping_add($v1);
+        $v1 = $v1.rotate_left(17);
+        $v1 ^= $v2;
+        $v2 = $v2.rotate_left(32);
+    };
+}
+
+/// SipHash-1-3 with a stable byte encoding for integers.
+#[derive(Clone, Debug)]
+pub struct StableHasher {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    tail: u64,
+    ntail: usize,
+    length: usize,
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(unused)]
+impl StableHasher {
+    pub fn new() -> Self {
+        Self::with_keys(0, 0)
+    }
+
+    pub fn with_keys(k0: u64, k1: u64) -> Self {
+        StableHasher {
+            v0: k0 ^ 0x736f_6d65_7073_6575,
+            v1: k1 ^ 0x646f_7261_6e64_6f6d,
+            v2: k0 ^ 0x6c79_6765_6e65_7261,
+            v3: k1 ^ 0x7465_6462_7974_6573,
+            tail: 0,
+            ntail: 0,
+            length: 0,
+        }
+    }
+
+    fn compress(&mut self, word: u64) {
+        self.v3 ^= word;
+        sip_round!(self.v0, self.v1, self.v2, self.v3);
+        self.v0 ^= word;
+    }
+}
+
+/// Reads up to 8 bytes as a little-endian u64.
+fn load_le(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .enumerate()
+        .fold(0, |word, (i, &byte)| word | (byte as u64) << (8 * i))
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        let (mut v0, mut v1, mut v2, mut v3) = (self.v0, self.v1, self.v2, self.v3);
+        let last = ((self.length as u64 & 0xff) << 56) | self.tail;
+        v3 ^= last;
+        sip_round!(v0, v1, v2, v3);
+        v0 ^= last;
+        v2 ^= 0xff;
+        for _ in 0..3 {
+            sip_round!(v0, v1, v2, v3);
+        }
+        v0 ^ v1 ^ v2 ^ v3
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.length += bytes.len();
+        if self.ntail != 0 {
+            let fill = (8 - self.ntail).min(bytes.len());
+            self.tail |= load_le(&bytes[..fill]) << (8 * self.ntail);
+            self.ntail += fill;
+            bytes = &bytes[fill..];
+            if self.ntail < 8 {
+                return;
+            }
+            self.compress(self.tail);
+            self.tail = 0;
+            self.ntail = 0;
+        }
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            self.compress(load_le(word));
+        }
+        let rest = words.remainder();
+        self.tail = load_le(rest);
+        self.ntail = rest.len();
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write(&[i]);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.write_u8(i as u8);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// rustc's FxHash: `hash = (hash.rotate_left(5) ^ word) * K` for every word written,
+/// followed by `fmix64` in `finish`.
+#[allow(unused)]
+#[derive(Clone, Debug, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+#[allow(unused)]
+impl FxHasher {
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED);
+    }
+}
+
+/// MurmurHash3's 64-bit finalizer.
+fn fmix64(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ hash >> 33
+}
+
+impl Hasher for FxHasher {
+    fn finish(&self) -> u64 {
+        fmix64(self.hash)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            self.add_to_hash(load_le(word));
+        }
+        let rest = words.remainder();
+        if !rest.is_empty() {
+            self.add_to_hash(load_le(rest));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hasher::{fmix64, FxHasher, StableHasher, FX_SEED};
+    use std::hash::{Hash, Hasher};
+
+    fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+        let mut hasher = StableHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_known_values() {
+        // 这些值一旦写入持久化文件就不能再变（与当前 std 的 SipHash-1-3 结果一致）
+        assert_eq!(hash(&0u64), 0xbd60_acb6_58c7_9e45);
+        assert_eq!(hash("hello"), 0xe037_876b_880b_8ed9);
+        assert_eq!(
+            hash(&[1u8, 2, 3, 4, 5, 6, 7, 8, 9][..]),
+            0xd1e9_bd33_cee6_85bd
+        );
+    }
+
+    #[test]
+    fn test_split_writes() {
+        let bytes: Vec<u8> = (0..100).collect();
+        let mut whole = StableHasher::with_keys(1, 2);
+        whole.write(&bytes);
+        for split in [0, 1, 3, 7, 8, 9, 15, 16, 17, 99] {
+            let mut parts = StableHasher::with_keys(1, 2);
+            parts.write(&bytes[..split]);
+            parts.write(&bytes[split..]);
+            assert_eq!(parts.finish(), whole.finish());
+        }
+    }
+
+    #[test]
+    fn test_integers_are_little_endian() {
+        let mut int = StableHasher::new();
+        int.write_u32(0x0102_0304);
+        let mut bytes = StableHasher::new();
+        bytes.write(&[4, 3, 2, 1]);
+        assert_eq!(int.finish(), bytes.finish());
+
+        let mut size = StableHasher::new();
+        size.write_usize(42);
+        let mut word = StableHasher::new();
+        word.write_u64(42);
+        assert_eq!(size.finish(), word.finish());
+    }
+
+    #[test]
+    fn test_fx_hasher() {
+        let mut hasher = FxHasher::default();
+        hasher.write_u64(1);
+        assert_eq!(hasher.hash, FX_SEED);
+        assert_eq!(hasher.finish(), fmix64(FX_SEED));
+        assert_eq!(fmix64(0), 0);
+
+        let mut whole = FxHasher::default();
+        whole.write(b"hello world");
+        let mut words = FxHasher::default();
+        words.write_u64(u64::from_le_bytes(*b"hello wo"));
+        words.write_u64(u64::from_le_bytes(*b"rld\0\0\0\0\0"));
+        assert_eq!(whole.finish(), words.finish());
+    }
+}
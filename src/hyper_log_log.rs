@@ -0,0 +1,204 @@
+// HyperLogLog（Flajolet 等）：用 2^p 个字节的寄存器估计数据流里不同元素的个数。
+//
+// 沿用布隆过滤器的两个独立哈希：h1(x) mod 2^p 选寄存器，h2(x) 前导零个数 + 1 作为秩，
+// 每个寄存器记录见过的最大秩。估计值为 α·m² / Σ2^(−M[j])，标准误差约 1.04 / √m。
+// 估计值较小且存在空寄存器时改用线性计数 m·ln(m / V)。
+// 两个 sketch 合并就是逐个寄存器取最大值，结果与把两边元素插入同一个 sketch 完全相同。
+
+use crate::bloom_filter::{get_index, DoubleHasher};
+use rand::random;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+const MIN_PRECISION: u8 = 4;
+const MAX_PRECISION: u8 = 18;
+
+#[allow(unused)]
+pub struct HyperLogLog<T: ?Sized> {
+    registers: Vec<u8>,
+    precision: u8,
+    hasher: DoubleHasher,
+    _phantom: PhantomData<T>,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> HyperLogLog<T> {
+    /// Uses `2^precision` registers.
+    pub fn new(precision: u8) -> Self {
+        Self::with_seeds(precision, [random(), random()])
+    }
+
+    /// Picks the smallest precision whose standard error is at most `std_error`.
+    pub fn with_std_error(std_error: f64) -> Self {
+        assert!(
+            std_error > 0f64 && std_error < 1f64,
+            "std_error must be in (0, 1), got {std_error}"
+        );
+        let precision = (1.04 / std_error).powi(2).log2().ceil() as u8;
+        Self::new(precision.clamp(MIN_PRECISION, MAX_PRECISION))
+    }
+
+    /// Same as `new`, but with fixed hash seeds so that sketches built on
+    /// different shards can be merged.
+    pub fn with_seeds(precision: u8, seeds: [u64; 2]) -> Self {
+        assert!(
+            (MIN_PRECISION..=MAX_PRECISION).contains(&precision),
+            "precision must be in {MIN_PRECISION}..={MAX_PRECISION}, got {precision}"
+        );
+        HyperLogLog {
+            registers: vec![0; 1 << precision],
+            precision,
+            hasher: DoubleHasher::with_seeds(seeds),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn insert(&mut self, value: &T) {
+        let hash_tuple = self.hasher.cal_hash(value);
+        let index = get_index(hash_tuple, 0, self.registers.len());
+        let rank = hash_tuple.1.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Estimated number of distinct values inserted.
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1f64 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-(register as i32)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Relative standard error of `estimate`: 1.04 / √m
+    pub fn std_error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    pub fn seeds(&self) -> [u64; 2] {
+        self.hasher.seeds()
+    }
+
+    /// Folds in a sketch built from another shard of the stream.
+    pub fn merge(&mut self, other: &Self) -> Result<(), HyperLogLogMergeError> {
+        if self.precision != other.precision {
+            return Err(HyperLogLogMergeError::Precision(
+                self.precision,
+                other.precision,
+            ));
+        }
+        if self.seeds() != other.seeds() {
+            return Err(HyperLogLogMergeError::Seeds);
+        }
+        for (register, &other_register) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(other_register);
+        }
+        Ok(())
+    }
+}
+
+/// Merging needs both sketches to map every value to the same register and rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HyperLogLogMergeError {
+    Precision(u8, u8),
+    Seeds,
+}
+
+impl Display for HyperLogLogMergeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HyperLogLogMergeError::Precision(left, right) => {
+                write!(f, "precisions differ: {left} vs {right}")
+            }
+            HyperLogLogMergeError::Seeds => write!(f, "hash seeds differ"),
+        }
+    }
+}
+
+impl Error for HyperLogLogMergeError {}
+
+#[cfg(test)]
+mod test {
+    use crate::hyper_log_log::{HyperLogLog, HyperLogLogMergeError};
+
+    #[test]
+    fn test_estimate() {
+        let mut sketch = HyperLogLog::new(14);
+        assert_eq!(sketch.estimate(), 0f64);
+        let mut inserted = 0;
+        for checkpoint in [100u64, 1_000, 10_000, 100_000, 1_000_000] {
+            while inserted < checkpoint {
+                sketch.insert(&inserted);
+                inserted += 1;
+            }
+            let error = (sketch.estimate() - checkpoint as f64).abs() / checkpoint as f64;
+            assert!(
+                error < 4f64 * sketch.std_error(),
+                "{checkpoint}: estimate {}, error {error}",
+                sketch.estimate()
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicates_do_not_count() {
+        let mut sketch = HyperLogLog::<str>::with_std_error(0.02);
+        assert_eq!(sketch.precision(), 12);
+        for _ in 0..100 {
+            for i in 0..1_000 {
+                sketch.insert(&format!("user-{i}"));
+            }
+        }
+        assert!((sketch.estimate() - 1_000f64).abs() < 1_000f64 * 4f64 * sketch.std_error());
+    }
+
+    #[test]
+    fn test_merge_shards() {
+        let seeds = [9, 10];
+        let mut whole = HyperLogLog::with_seeds(12, seeds);
+        let mut left = HyperLogLog::with_seeds(12, seeds);
+        let mut right = HyperLogLog::with_seeds(12, seeds);
+        for key in 0..60_000u64 {
+            whole.insert(&key);
+            left.insert(&key);
+        }
+        for key in 40_000..100_000u64 {
+            whole.insert(&key);
+            right.insert(&key);
+        }
+        left.merge(&right).unwrap();
+        assert_eq!(left.registers, whole.registers);
+        assert_eq!(left.estimate(), whole.estimate());
+
+        let other = HyperLogLog::with_seeds(12, [1, 2]);
+        assert_eq!(left.merge(&other), Err(HyperLogLogMergeError::Seeds));
+        let coarse = HyperLogLog::with_seeds(10, seeds);
+        assert_eq!(
+            left.merge(&coarse),
+            Err(HyperLogLogMergeError::Precision(12, 10))
+        );
+    }
+}
@@ -0,0 +1,296 @@
+// 索引优先队列：元素用 0..n 的整数 id 标识，每个 id 在队列中最多出现一次。
+//
+// 在二叉堆之外再维护一张 positions 表，记录每个 id 当前在堆中的位置，
+// 这样就能在 O(1) 时间找到某个 id，在 O(log n) 时间修改它的优先级或把它删掉。
+// 堆中元素移动时必须同步更新 positions：上浮、下沉直接用 binary_heap 的 sift 函数，
+// 通过它的回调记下每个 id 的新位置。
+
+use crate::binary_heap::{parent, sift_down_tracked, sift_up_tracked, Compare, MinOrder};
+use std::cmp::Ordering;
+
+// 堆里存的是 (id, priority)，只按 priority 比较
+struct ByPriority<'a, C>(&'a C);
+
+impl<P, C: Compare<P>> Compare<(usize, P)> for ByPriority<'_, C> {
+    fn compare(&self, a: &(usize, P), b: &(usize, P)) -> Ordering {
+        self.0.compare(&a.1, &b.1)
+    }
+}
+
+#[allow(unused)]
+pub struct IndexedPriorityQueue<P, C = MinOrder> {
+    // 位置 i 的 (id, priority) 存放在 heap[i - 1]
+    heap: Vec<(usize, P)>,
+    // positions[id] 为 id 在堆中的位置（从 1 开始），不在队列中为 None
+    positions: Vec<Option<usize>>,
+    cmp: C,
+}
+
+#[allow(unused)]
+impl<P: Ord> IndexedPriorityQueue<P> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+
+    /// Queue with room for ids in `0..ids` without reallocating.
+    pub fn with_capacity(ids: usize) -> Self {
+        Self::with_capacity_and_comparator(ids, MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<P, C: Compare<P>> IndexedPriorityQueue<P, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        IndexedPriorityQueue {
+            heap: Vec::new(),
+            positions: Vec::new(),
+            cmp,
+        }
+    }
+
+    pub fn with_capacity_and_comparator(ids: usize, cmp: C) -> Self {
+        IndexedPriorityQueue {
+            heap: Vec::with_capacity(ids),
+            positions: vec![None; ids],
+            cmp,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    fn position(&self, id: usize) -> Option<usize> {
+        self.positions.get(id).copied().flatten()
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.position(id).is_some()
+    }
+
+    /// Current priority of `id`, if it is queued.
+    pub fn priority(&self, id: usize) -> Option<&P> {
+        self.position(id).map(|index| &self.heap[index - 1].1)
+    }
+
+    /// The `(id, priority)` pair `pop` would return next.
+    pub fn peek(&self) -> Option<(usize, &P)> {
+        self.heap.first().map(|(id, priority)| (*id, priority))
+    }
+
+    fn cmp_at(&self, index: usize, other: usize) -> Ordering {
+        self.cmp
+            .compare(&self.heap[index - 1].1, &self.heap[other - 1].1)
+    }
+
+    fn swap(&mut self, index: usize, other: usize) {
+        self.heap.swap(index - 1, other - 1);
+        self.positions[self.heap[index - 1].0] = Some(index);
+        self.positions[self.heap[other - 1].0] = Some(other);
+    }
+
+    fn move_up(&mut self, index: usize) {
+        let positions = &mut self.positions;
+        sift_up_tracked::<2, _, _, _>(
+            &mut self.heap,
+            &ByPriority(&self.cmp),
+            index,
+            |&(id, _), index| positions[id] = Some(index),
+        );
+    }
+
+    fn move_down(&mut self, index: usize) {
+        let positions = &mut self.positions;
+        sift_down_tracked::<2, _, _, _>(
+            &mut self.heap,
+            &ByPriority(&self.cmp),
+            index,
+            |&(id, _), index| positions[id] = Some(index),
+        );
+    }
+
+    // 位置 index 的优先级变了，往上或往下调整到合适的位置
+    fn restore(&mut self, index: usize) {
+        if index > 1 {
+            let parent_index = parent!(index);
+            if let Ordering::Less = self.cmp_at(index, parent_index) {
+                self.move_up(index);
+                return;
+            }
+        }
+        self.move_down(index);
+    }
+
+    /// Queues `id` with `priority`, or moves it forward if `priority` pops before
+    /// its current one. Returns `false` if the queue was left unchanged.
+    pub fn push_or_decrease(&mut self, id: usize, priority: P) -> bool {
+        match self.position(id) {
+            Some(index) => {
+                if let Ordering::Less = self.cmp.compare(&priority, &self.heap[index - 1].1) {
+                    self.heap[index - 1].1 = priority;
+                    self.move_up(index);
+                    true
+                } else {
+                    false
+                }
+            }
+            None => {
+                if id >= self.positions.len() {
+                    self.positions.resize(id + 1, None);
+                }
+                self.heap.push((id, priority));
+                let index = self.len();
+                self.positions[id] = Some(index);
+                self.move_up(index);
+                true
+            }
+        }
+    }
+
+    /// Sets the priority of a queued `id` in either direction and returns the old one,
+    /// or `None` if `id` is not queued.
+    pub fn change_priority(&mut self, id: usize, priority: P) -> Option<P> {
+        let index = self.position(id)?;
+        let old = std::mem::replace(&mut self.heap[index - 1].1, priority);
+        self.restore(index);
+        Some(old)
+    }
+
+    /// Removes `id` from the queue and returns its priority.
+    pub fn remove(&mut self, id: usize) -> Option<P> {
+        let index = self.position(id)?;
+        let size = self.len();
+        if index != size {
+            self.swap(index, size);
+        }
+        let (_, priority) = self.heap.pop().expect("queued id has a heap entry");
+        self.positions[id] = None;
+        if index != size {
+            self.restore(index);
+        }
+        Some(priority)
+    }
+
+    pub fn pop(&mut self) -> Option<(usize, P)> {
+        let id = self.peek()?.0;
+        self.remove(id).map(|priority| (id, priority))
+    }
+
+    pub fn clear(&mut self) {
+        for (id, _) in self.heap.drain(..) {
+            self.positions[id] = None;
+        }
+    }
+}
+
+impl<P: Ord> Default for IndexedPriorityQueue<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary_heap::MaxOrder;
+    use crate::indexed_priority_queue::IndexedPriorityQueue;
+    use rand::{thread_rng, Rng};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut queue = IndexedPriorityQueue::new();
+        for (id, priority) in [(0, 5), (1, 3), (2, 8), (3, 1)] {
+            assert!(queue.push_or_decrease(id, priority));
+        }
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.peek(), Some((3, &1)));
+        assert_eq!(queue.pop(), Some((3, 1)));
+        assert_eq!(queue.pop(), Some((1, 3)));
+        assert!(!queue.contains(1));
+        assert_eq!(queue.pop(), Some((0, 5)));
+        assert_eq!(queue.pop(), Some((2, 8)));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_push_or_decrease() {
+        let mut queue = IndexedPriorityQueue::new();
+        queue.push_or_decrease(7, 10);
+        queue.push_or_decrease(2, 6);
+        // 优先级更差的不会覆盖
+        assert!(!queue.push_or_decrease(7, 12));
+        assert_eq!(queue.priority(7), Some(&10));
+        assert!(queue.push_or_decrease(7, 4));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some((7, 4)));
+        assert_eq!(queue.pop(), Some((2, 6)));
+    }
+
+    #[test]
+    fn test_change_priority_and_remove() {
+        let mut queue = IndexedPriorityQueue::with_comparator(MaxOrder);
+        for id in 0..10 {
+            queue.push_or_decrease(id, id);
+        }
+        assert_eq!(queue.change_priority(9, 0), Some(9));
+        assert_eq!(queue.change_priority(0, 20), Some(0));
+        assert_eq!(queue.change_priority(42, 1), None);
+        assert_eq!(queue.remove(5), Some(5));
+        assert_eq!(queue.remove(5), None);
+        assert!(!queue.contains(5));
+        let mut popped = Vec::new();
+        while let Some((id, _)) = queue.pop() {
+            popped.push(id);
+        }
+        assert_eq!(popped, [0, 8, 7, 6, 4, 3, 2, 1, 9]);
+    }
+
+    // 随机操作，与 HashMap 记录的优先级对比
+    #[test]
+    fn test_random_operations() {
+        let mut rng = thread_rng();
+        let ids = 200;
+        let mut queue = IndexedPriorityQueue::with_capacity(ids);
+        let mut oracle: HashMap<usize, u32> = HashMap::new();
+        for _ in 0..20_000 {
+            let id = rng.gen_range(0..ids);
+            let priority = rng.gen_range(0..1_000);
+            match rng.gen_range(0..4) {
+                0 => {
+                    let changed = queue.push_or_decrease(id, priority);
+                    let old = oracle.get(&id).copied();
+                    assert_eq!(changed, old.is_none_or(|old| priority < old));
+                    if changed {
+                        oracle.insert(id, priority);
+                    }
+                }
+                1 => {
+                    let old = queue.change_priority(id, priority);
+                    assert_eq!(old, oracle.get(&id).copied());
+                    if old.is_some() {
+                        oracle.insert(id, priority);
+                    }
+                }
+                2 => assert_eq!(queue.remove(id), oracle.remove(&id)),
+                _ => {
+                    let min = oracle.values().min().copied();
+                    let popped = queue.pop();
+                    assert_eq!(popped.map(|(_, priority)| priority), min);
+                    if let Some((id, priority)) = popped {
+                        assert_eq!(oracle.remove(&id), Some(priority));
+                    }
+                }
+            }
+            assert_eq!(queue.len(), oracle.len());
+            assert_eq!(queue.contains(id), oracle.contains_key(&id));
+        }
+        queue.clear();
+        assert!(queue.is_empty());
+        assert!((0..ids).all(|id| !queue.contains(id)));
+    }
+}
@@ -0,0 +1,152 @@
+// 多路归并：把 N 个已经排好序的迭代器惰性地合并成一个有序的迭代器。
+//
+// 堆里只放每一路当前的第一个元素，大小不超过 N。每次取出堆顶，再从它所在的那一路补一个
+// 元素进来，所以合并 n 个元素共 O(n log N)。补元素时直接替换堆顶再下沉，省掉一次上浮。
+// 元素相等时先出编号小的那一路，结果是稳定的。
+
+use crate::binary_heap::{BinaryHeap, Compare, MinOrder, PeekMut};
+use std::cmp::Ordering;
+
+struct Head<T> {
+    item: T,
+    source: usize,
+}
+
+// 先按元素比较，相等时按来源编号
+struct HeadOrder<C>(C);
+
+impl<T, C: Compare<T>> Compare<Head<T>> for HeadOrder<C> {
+    fn compare(&self, a: &Head<T>, b: &Head<T>) -> Ordering {
+        self.0
+            .compare(&a.item, &b.item)
+            .then(a.source.cmp(&b.source))
+    }
+}
+
+#[allow(unused)]
+pub struct KWayMerge<I: Iterator, C = MinOrder> {
+    sources: Vec<I>,
+    heap: BinaryHeap<Head<I::Item>, HeadOrder<C>>,
+}
+
+#[allow(unused)]
+impl<I: Iterator> KWayMerge<I>
+where
+    I::Item: Ord,
+{
+    /// Merges iterators that are each sorted in ascending order.
+    pub fn new<S: IntoIterator<IntoIter = I, Item = I::Item>>(
+        sources: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Self::with_comparator(sources, MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<I: Iterator, C: Compare<I::Item>> KWayMerge<I, C> {
+    /// Merges iterators that are each sorted so that `cmp` is ascending.
+    pub fn with_comparator<S: IntoIterator<IntoIter = I, Item = I::Item>>(
+        sources: impl IntoIterator<Item = S>,
+        cmp: C,
+    ) -> Self {
+        let mut sources: Vec<I> = sources.into_iter().map(IntoIterator::into_iter).collect();
+        let heads = sources
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(source, iter)| iter.next().map(|item| Head { item, source }))
+            .collect();
+        KWayMerge {
+            sources,
+            heap: BinaryHeap::from_vec_with_comparator(heads, HeadOrder(cmp)),
+        }
+    }
+}
+
+impl<I: Iterator, C: Compare<I::Item>> Iterator for KWayMerge<I, C> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let mut top = self.heap.peek_mut()?;
+        let source = top.source;
+        match self.sources[source].next() {
+            Some(item) => Some(std::mem::replace(&mut top.item, item)),
+            None => Some(PeekMut::pop(top).item),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sources.iter().fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |(low, high), iter| {
+                let (iter_low, iter_high) = iter.size_hint();
+                (
+                    low.saturating_add(iter_low),
+                    high.zip(iter_high)
+                        .and_then(|(high, iter_high)| high.checked_add(iter_high)),
+                )
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary_heap::{KeyOrder, MaxOrder};
+    use crate::k_way_merge::KWayMerge;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_merge() {
+        let merged: Vec<_> =
+            KWayMerge::new([vec![1, 4, 7], vec![], vec![2, 5, 8, 9], vec![3, 6]]).collect();
+        assert_eq!(merged, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let empty: Vec<Vec<u32>> = Vec::new();
+        assert_eq!(KWayMerge::new(empty).next(), None);
+    }
+
+    #[test]
+    fn test_descending_and_size_hint() {
+        let merge = KWayMerge::with_comparator([vec![9, 5, 1], vec![8, 2], vec![7]], MaxOrder);
+        assert_eq!(merge.size_hint(), (6, Some(6)));
+        assert_eq!(merge.collect::<Vec<_>>(), [9, 8, 7, 5, 2, 1]);
+    }
+
+    // 元素相等时按来源顺序输出
+    #[test]
+    fn test_stable_across_sources() {
+        let sources = (0..5).map(|source| (0..100).map(move |key| (key / 10, source)));
+        let merge = KWayMerge::with_comparator(sources, KeyOrder(|item: &(u32, u32)| item.0));
+        let merged: Vec<_> = merge.collect();
+        let mut expected = merged.clone();
+        expected.sort();
+        assert_eq!(merged, expected);
+    }
+
+    // 惰性合并：只消费需要的部分
+    #[test]
+    fn test_lazy() {
+        let merge = KWayMerge::new([(0u64..).step_by(2), (1u64..).step_by(2)]);
+        assert_eq!(merge.take(5).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_million_items() {
+        let mut rng = thread_rng();
+        for (ways, len) in [(2, 1_000_000), (100, 20_000), (10_000, 200)] {
+            let sources: Vec<Vec<u64>> = (0..ways)
+                .map(|_| {
+                    let mut source: Vec<u64> = (0..rng.gen_range(0..=len))
+                        .map(|_| rng.gen_range(0..1_000_000))
+                        .collect();
+                    source.sort_unstable();
+                    source
+                })
+                .collect();
+            let mut expected = sources.concat();
+            expected.sort_unstable();
+            let merge = KWayMerge::new(sources);
+            assert_eq!(merge.size_hint().0, expected.len());
+            assert_eq!(merge.collect::<Vec<_>>(), expected);
+        }
+    }
+}
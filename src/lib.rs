@@ -1,8 +1,30 @@
 mod binary_heap;
+mod binomial_heap;
+mod blocked_bloom_filter;
 mod bloom_filter;
 mod bst_new;
+mod codec;
+mod concurrent_bloom_filter;
+mod count_min_sketch;
+mod counting_bloom_filter;
+mod cuckoo_filter;
+mod dary_heap;
 mod dijkstra;
 mod exercise;
+mod hasher;
+mod hyper_log_log;
+mod indexed_priority_queue;
+mod k_way_merge;
 mod link_list;
 mod lis;
+mod membership;
+mod min_max_heap;
+mod pairing_heap;
+mod radix_heap;
+mod rotating_bloom_filter;
+mod running_median;
+mod scalable_bloom_filter;
+mod stable_binary_heap;
+mod top_k;
 mod trie;
+mod xor_filter;
@@ -96,7 +96,7 @@ pub fn max_envelopes_1(envelopes: Vec<Vec<i32>>) -> i32 {
 
 #[cfg(test)]
 mod test {
-    use crate::lis::{lis, max_envelopes, max_envelopes_1};
+    use crate::lis::max_envelopes_1;
 
     #[test]
     fn test() {
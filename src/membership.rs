@@ -0,0 +1,66 @@
+// 近似成员查询：可能把不存在的元素误判为存在，但绝不会把存在的元素判为不存在。
+//
+// 各种过滤器都用 (容量, 误差率) 构造，实现这个 trait 之后可以互相替换。
+
+use crate::bloom_filter::BloomFilter;
+use crate::counting_bloom_filter::CountingBloomFilter;
+use crate::scalable_bloom_filter::ScalableBloomFilter;
+use std::hash::Hash;
+
+#[allow(unused)]
+pub trait ApproximateMembership<T: ?Sized> {
+    fn new(cap: usize, err_rate: f64) -> Self
+    where
+        Self: Sized;
+
+    /// Returns `false` if the filter had no room left for `value`.
+    fn insert(&mut self, value: &T) -> bool;
+
+    fn contains(&self, value: &T) -> bool;
+}
+
+impl<T: ?Sized + Hash> ApproximateMembership<T> for BloomFilter<T> {
+    fn new(cap: usize, err_rate: f64) -> Self {
+        BloomFilter::new(cap, err_rate)
+    }
+
+    fn insert(&mut self, value: &T) -> bool {
+        BloomFilter::insert(self, value);
+        true
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        BloomFilter::contains(self, value)
+    }
+}
+
+impl<T: ?Sized + Hash> ApproximateMembership<T> for CountingBloomFilter<T> {
+    fn new(cap: usize, err_rate: f64) -> Self {
+        CountingBloomFilter::new(cap, err_rate)
+    }
+
+    fn insert(&mut self, value: &T) -> bool {
+        CountingBloomFilter::insert(self, value);
+        true
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        CountingBloomFilter::contains(self, value)
+    }
+}
+
+impl<T: ?Sized + Hash> ApproximateMembership<T> for ScalableBloomFilter<T> {
+    fn new(cap: usize, err_rate: f64) -> Self {
+        ScalableBloomFilter::new(cap, err_rate)
+    }
+
+    fn insert(&mut self, value: &T) -> bool {
+        // 返回 false 只表示已经存在，元素依然在过滤器里
+        ScalableBloomFilter::insert(self, value);
+        true
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        ScalableBloomFilter::contains(self, value)
+    }
+}
@@ -0,0 +1,310 @@
+// 最小-最大堆（Atkinson 等，Min-Max Heaps and Generalized Priority Queues）。
+//
+// 完全二叉树按层交替：偶数层（根在第 0 层）是最小层，节点不大于它所有的后代；奇数层是
+// 最大层，节点不小于它所有的后代。所以最小值是根，最大值是根的两个孩子之一，两端都能 O(1)
+// 查看、O(log n) 删除。
+//
+// 插入时先和父节点比较决定走最小层还是最大层，然后只和祖父节点比较往上浮；删除时把末尾元素
+// 放到空位，在孩子和孙子中找最靠前（最小层）或最靠后（最大层）的一个往下沉。
+// 这里位置从 0 开始编号：位置 i 的子节点为 2i + 1、2i + 2，孙子节点为 4i + 3 ..= 4i + 6。
+
+use crate::binary_heap::{Compare, MinOrder};
+use std::cmp::Ordering;
+
+#[allow(unused)]
+pub struct MinMaxHeap<T, C = MinOrder> {
+    data: Vec<T>,
+    cmp: C,
+}
+
+fn is_min_level(index: usize) -> bool {
+    (index + 1).ilog2().is_multiple_of(2)
+}
+
+#[allow(unused)]
+impl<T: Ord> MinMaxHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_comparator(capacity, MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T, C: Compare<T>> MinMaxHeap<T, C> {
+    /// Heap whose "min" end is the element that `cmp` orders first.
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::with_capacity_and_comparator(0, cmp)
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        MinMaxHeap {
+            data: Vec::with_capacity(capacity),
+            cmp,
+        }
+    }
+
+    /// Builds a heap from `data` in O(n) by bottom-up trickle down.
+    pub fn from_vec_with_comparator(data: Vec<T>, cmp: C) -> Self {
+        let mut heap = MinMaxHeap { data, cmp };
+        for index in (0..heap.data.len() / 2).rev() {
+            heap.trickle_down(index);
+        }
+        heap
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    // 最小层上要找 Less 的一方，最大层上要找 Greater 的一方
+    fn is_before(&self, index: usize, other: usize, want: Ordering) -> bool {
+        self.cmp.compare(&self.data[index], &self.data[other]) == want
+    }
+
+    fn bubble_up_grandparents(&mut self, mut index: usize, want: Ordering) {
+        while index > 2 {
+            let grandparent = ((index - 1) / 2 - 1) / 2;
+            if self.is_before(index, grandparent, want) {
+                self.data.swap(index, grandparent);
+                index = grandparent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn bubble_up(&mut self, index: usize) {
+        if index == 0 {
+            return;
+        }
+        let parent = (index - 1) / 2;
+        // 新元素放在最小层上却比父节点（最大层）还大，就该去最大层，反之亦然
+        let want = if is_min_level(index) {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+        if self.is_before(parent, index, want) {
+            self.data.swap(index, parent);
+            self.bubble_up_grandparents(parent, want.reverse());
+        } else {
+            self.bubble_up_grandparents(index, want);
+        }
+    }
+
+    fn trickle_down(&mut self, mut index: usize) {
+        let want = if is_min_level(index) {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+        let size = self.len();
+        loop {
+            let first_child = 2 * index + 1;
+            if first_child >= size {
+                break;
+            }
+            // 在孩子和孙子中找出最靠前（或最靠后）的一个
+            let first_grandchild = 4 * index + 3;
+            let candidates = (first_child..(first_child + 2).min(size))
+                .chain(first_grandchild..(first_grandchild + 4).min(size));
+            let mut best = first_child;
+            for candidate in candidates {
+                if self.is_before(candidate, best, want) {
+                    best = candidate;
+                }
+            }
+            if !self.is_before(best, index, want) {
+                break;
+            }
+            self.data.swap(best, index);
+            if best < first_grandchild {
+                break;
+            }
+            // 换下去的元素可能和孙子的父节点（另一种层）顺序颠倒
+            let parent = (best - 1) / 2;
+            if self.is_before(parent, best, want) {
+                self.data.swap(best, parent);
+            }
+            index = best;
+        }
+    }
+
+    pub fn push(&mut self, val: T) {
+        self.data.push(val);
+        self.bubble_up(self.data.len() - 1);
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        match self.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => {
+                if self.is_before(2, 1, Ordering::Greater) {
+                    Some(2)
+                } else {
+                    Some(1)
+                }
+            }
+        }
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_index().map(|index| &self.data[index])
+    }
+
+    fn remove_at(&mut self, index: usize) -> T {
+        let val = self.data.swap_remove(index);
+        if index < self.data.len() {
+            self.trickle_down(index);
+        }
+        val
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0))
+        }
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.max_index().map(|index| self.remove_at(index))
+    }
+
+    /// Iterates over the elements in arbitrary (storage) order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<T: Ord> Default for MinMaxHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for MinMaxHeap<T> {
+    fn from(data: Vec<T>) -> Self {
+        Self::from_vec_with_comparator(data, MinOrder)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinMaxHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary_heap::KeyOrder;
+    use crate::min_max_heap::MinMaxHeap;
+    use rand::{thread_rng, Rng};
+    use std::cmp::Reverse;
+
+    #[test]
+    fn test_both_ends() {
+        let mut heap = MinMaxHeap::new();
+        for val in [2, 8, 1, 9, 5, 4, 6, 3, 5] {
+            heap.push(val);
+        }
+        assert_eq!(heap.len(), 9);
+        assert_eq!(heap.peek_min(), Some(&1));
+        assert_eq!(heap.peek_max(), Some(&9));
+        assert_eq!(heap.pop_max(), Some(9));
+        assert_eq!(heap.pop_min(), Some(1));
+        assert_eq!(heap.pop_max(), Some(8));
+        assert_eq!(heap.pop_min(), Some(2));
+        let mut rest = Vec::new();
+        while let Some(val) = heap.pop_min() {
+            rest.push(val);
+        }
+        assert_eq!(rest, [3, 4, 5, 5, 6]);
+        assert_eq!(heap.pop_max(), None);
+        assert_eq!(heap.peek_min(), None);
+    }
+
+    #[test]
+    fn test_from_vec() {
+        for len in [0, 1, 2, 3, 4, 7, 8, 100, 1_000] {
+            let data: Vec<u32> = (0..len).map(|_| thread_rng().gen_range(0..100)).collect();
+            let mut heap = MinMaxHeap::from(data.clone());
+            let mut sorted = data;
+            sorted.sort();
+            let mut popped = Vec::new();
+            while let Some(val) = heap.pop_max() {
+                popped.push(val);
+            }
+            popped.reverse();
+            assert_eq!(popped, sorted);
+        }
+    }
+
+    #[test]
+    fn test_comparator() {
+        let mut heap = MinMaxHeap::with_comparator(KeyOrder(|word: &&str| Reverse(word.len())));
+        for word in ["pear", "fig", "banana", "kiwi"] {
+            heap.push(word);
+        }
+        assert_eq!(heap.peek_min(), Some(&"banana"));
+        assert_eq!(heap.peek_max(), Some(&"fig"));
+    }
+
+    // 每次操作后两端都与排好序的 Vec 对比
+    #[test]
+    fn test_random_against_sorted_vec() {
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            let initial: Vec<u32> = (0..rng.gen_range(0..200))
+                .map(|_| rng.gen_range(0..500))
+                .collect();
+            let mut heap: MinMaxHeap<_> = initial.iter().copied().collect();
+            let mut oracle = initial;
+            oracle.sort();
+            for _ in 0..5_000 {
+                match rng.gen_range(0..4) {
+                    0 | 1 => {
+                        let val = rng.gen_range(0..500);
+                        heap.push(val);
+                        let index = oracle.partition_point(|other| *other < val);
+                        oracle.insert(index, val);
+                    }
+                    2 => assert_eq!(
+                        heap.pop_min(),
+                        (!oracle.is_empty()).then(|| oracle.remove(0))
+                    ),
+                    _ => assert_eq!(heap.pop_max(), oracle.pop()),
+                }
+                assert_eq!(heap.len(), oracle.len());
+                assert_eq!(heap.peek_min(), oracle.first());
+                assert_eq!(heap.peek_max(), oracle.last());
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+// 配对堆：一棵满足堆序的多叉树，用“左孩子、右兄弟”的方式存储。
+//
+// push 和 meld 只是把两棵树的根比较一次，输的一方成为赢的一方的第一个孩子，都是 O(1)。
+// pop 删掉根之后，把它的所有孩子按两趟合并：第一趟从左到右两两合并，第二趟从右到左依次
+// 合并成一棵树，均摊 O(log n)。decrease_key 把节点连同子树从原位置剪下来，再和根合并。
+//
+// 节点用 Rc<RefCell<_>> 连接，堆持有所有强引用，句柄只持有 Weak，元素出堆后句柄自动失效。
+
+use crate::binary_heap::{Compare, FnOrder, KeyOrder, MaxOrder, MinOrder};
+use std::cell::{Ref, RefCell};
+use std::cmp::Ordering;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+struct Node<T> {
+    value: T,
+    child: Link<T>,
+    sibling: Link<T>,
+    // 第一个孩子指向父节点，其余孩子指向左边的兄弟，根节点为空
+    prev: Weak<RefCell<Node<T>>>,
+}
+
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+
+/// Refers to a value pushed into a `PairingHeap`, for `decrease_key`.
+pub struct Handle<T>(Weak<RefCell<Node<T>>>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(self.0.clone())
+    }
+}
+
+#[allow(unused)]
+pub struct PairingHeap<T, C = MinOrder> {
+    root: Link<T>,
+    len: usize,
+    cmp: C,
+}
+
+#[allow(unused)]
+impl<T: Ord> PairingHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T: Ord> PairingHeap<T, MaxOrder> {
+    pub fn new_max() -> Self {
+        Self::with_comparator(MaxOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T, F: Fn(&T, &T) -> Ordering> PairingHeap<T, FnOrder<F>> {
+    /// Heap ordered by `cmp`, where `Less` means the first argument pops first.
+    pub fn by(cmp: F) -> Self {
+        Self::with_comparator(FnOrder(cmp))
+    }
+}
+
+#[allow(unused)]
+impl<T, K: Ord, F: Fn(&T) -> K> PairingHeap<T, KeyOrder<F>> {
+    /// Heap that pops the element with the smallest `key` first.
+    pub fn by_key(key: F) -> Self {
+        Self::with_comparator(KeyOrder(key))
+    }
+}
+
+#[allow(unused)]
+impl<T, C: Compare<T>> PairingHeap<T, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        PairingHeap {
+            root: None,
+            len: 0,
+            cmp,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 两棵树的根比较一次，输的一方成为赢的一方的第一个孩子
+    fn link(&self, a: Rc<RefCell<Node<T>>>, b: Rc<RefCell<Node<T>>>) -> Rc<RefCell<Node<T>>> {
+        let ordering = self.cmp.compare(&a.borrow().value, &b.borrow().value);
+        let (winner, loser) = match ordering {
+            Ordering::Greater => (b, a),
+            _ => (a, b),
+        };
+        {
+            let mut winner_node = winner.borrow_mut();
+            let mut loser_node = loser.borrow_mut();
+            if let Some(child) = winner_node.child.take() {
+                child.borrow_mut().prev = Rc::downgrade(&loser);
+                loser_node.sibling = Some(child);
+            }
+            loser_node.prev = Rc::downgrade(&winner);
+        }
+        winner.borrow_mut().child = Some(loser);
+        winner
+    }
+
+    fn meld_root(&mut self, tree: Rc<RefCell<Node<T>>>) {
+        self.root = match self.root.take() {
+            None => Some(tree),
+            Some(root) => Some(self.link(root, tree)),
+        };
+    }
+
+    /// Pushes `value` in O(1) and returns a handle to it.
+    pub fn push(&mut self, value: T) -> Handle<T> {
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            child: None,
+            sibling: None,
+            prev: Weak::new(),
+        }));
+        let handle = Handle(Rc::downgrade(&node));
+        self.meld_root(node);
+        self.len += 1;
+        handle
+    }
+
+    /// The element `pop` would return next.
+    pub fn peek(&self) -> Option<Ref<'_, T>> {
+        self.root
+            .as_ref()
+            .map(|root| Ref::map(root.borrow(), |node| &node.value))
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root.take()?;
+        self.len -= 1;
+        let mut node = Rc::into_inner(root)
+            .expect("handles only hold weak references")
+            .into_inner();
+
+        let mut trees = Vec::new();
+        let mut next = node.child.take();
+        while let Some(child) = next {
+            {
+                let mut child_node = child.borrow_mut();
+                next = child_node.sibling.take();
+                child_node.prev = Weak::new();
+            }
+            trees.push(child);
+        }
+
+        // 第一趟从左到右两两合并，第二趟从右到左依次合并
+        let mut paired = Vec::with_capacity(trees.len().div_ceil(2));
+        let mut trees = trees.into_iter();
+        while let Some(first) = trees.next() {
+            match trees.next() {
+                Some(second) => paired.push(self.link(first, second)),
+                None => paired.push(first),
+            }
+        }
+        self.root = paired
+            .into_iter()
+            .rev()
+            .reduce(|merged, tree| self.link(tree, merged));
+        Some(node.value)
+    }
+
+    /// Moves all elements of `other` into `self` in O(1). Handles into `other` stay valid
+    /// and now refer to `self`.
+    pub fn meld(&mut self, mut other: Self) {
+        self.len += mem::take(&mut other.len);
+        if let Some(tree) = other.root.take() {
+            self.meld_root(tree);
+        }
+    }
+
+    /// Replaces the value behind `handle` with `value` in amortized O(log n). Returns `false`
+    /// and leaves the heap unchanged if `value` would pop after the current value, or if
+    /// that value has already been popped. `handle` must come from this heap.
+    pub fn decrease_key(&mut self, handle: &Handle<T>, value: T) -> bool {
+        let node = match handle.0.upgrade() {
+            Some(node) => node,
+            None => return false,
+        };
+        if let Ordering::Greater = self.cmp.compare(&value, &node.borrow().value) {
+            return false;
+        }
+        node.borrow_mut().value = value;
+        let prev = node.borrow().prev.upgrade();
+        let prev = match prev {
+            Some(prev) => prev,
+            // 根节点不需要调整
+            None => return true,
+        };
+
+        // 把以 node 为根的子树剪下来，右边的兄弟接到 prev 上
+        let sibling = node.borrow_mut().sibling.take();
+        if let Some(sibling) = &sibling {
+            sibling.borrow_mut().prev = Rc::downgrade(&prev);
+        }
+        {
+            let mut prev_node = prev.borrow_mut();
+            let is_first_child = prev_node
+                .child
+                .as_ref()
+                .is_some_and(|child| Rc::ptr_eq(child, &node));
+            if is_first_child {
+                prev_node.child = sibling;
+            } else {
+                prev_node.sibling = sibling;
+            }
+        }
+        node.borrow_mut().prev = Weak::new();
+        self.meld_root(node);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        drop_tree(self.root.take());
+        self.len = 0;
+    }
+}
+
+// 兄弟链表可能很长，递归释放会栈溢出，这里用栈逐个拆开
+fn drop_tree<T>(root: Link<T>) {
+    let mut stack: Vec<_> = root.into_iter().collect();
+    while let Some(node) = stack.pop() {
+        let mut node = node.borrow_mut();
+        stack.extend(node.child.take());
+        stack.extend(node.sibling.take());
+    }
+}
+
+impl<T, C> Drop for PairingHeap<T, C> {
+    fn drop(&mut self) {
+        drop_tree(self.root.take());
+    }
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary_heap::heap_test_suite;
+    use crate::pairing_heap::{Handle, PairingHeap};
+
+    heap_test_suite!(PairingHeap, Handle);
+
+    // 递增插入时根的孩子是一条很长的兄弟链表，释放时不能递归
+    #[test]
+    fn test_drop_long_sibling_chain() {
+        let mut heap = PairingHeap::new();
+        for val in 0..1_000_000 {
+            heap.push(val);
+        }
+        assert_eq!(heap.len(), 1_000_000);
+        drop(heap);
+    }
+}
@@ -0,0 +1,190 @@
+// 基数堆（Ahuja 等，Faster Algorithms for the Shortest Path Problem）：只适用于单调的整数键，
+// 也就是新插入的键不小于最近一次弹出的键，Dijkstra 正好满足这一点。
+//
+// 记 last 为最近一次弹出的键，键 k 放进第 b 个桶，b 为 k ^ last 的有效位数（k == last 时为 0）。
+// 第 0 个桶里的键都等于 last，可以直接弹出；第 0 个桶空了就找第一个非空的桶，取其中最小的键
+// 作为新的 last，把这个桶里的元素重新分到更低的桶里。每个元素只会往低处移动，最多移动
+// BITS 次，所以 push 为 O(1)，pop 均摊 O(BITS)，而且不做任何元素之间的比较。
+
+use std::mem;
+
+/// Unsigned integer keys usable in a `RadixHeap`.
+pub trait RadixKey: Copy + Ord {
+    const BITS: u32;
+    const ZERO: Self;
+
+    /// Number of significant bits in `self ^ other`.
+    fn radix_distance(self, other: Self) -> u32;
+}
+
+macro_rules! radix_key {
+    ($($t: ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BITS: u32 = <$t>::BITS;
+                const ZERO: Self = 0;
+
+                fn radix_distance(self, other: Self) -> u32 {
+                    Self::BITS - (self ^ other).leading_zeros()
+                }
+            }
+        )*
+    };
+}
+
+radix_key!(u8, u16, u32, u64, u128, usize);
+
+#[allow(unused)]
+pub struct RadixHeap<K, V> {
+    // buckets[b] 中的键与 last 的异或有 b 个有效位
+    buckets: Vec<Vec<(K, V)>>,
+    last: K,
+    len: usize,
+}
+
+#[allow(unused)]
+impl<K: RadixKey, V> RadixHeap<K, V> {
+    pub fn new() -> Self {
+        RadixHeap {
+            buckets: (0..=K::BITS).map(|_| Vec::new()).collect(),
+            last: K::ZERO,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The most recently popped key; pushed keys must not be smaller than it.
+    pub fn last_key(&self) -> K {
+        self.last
+    }
+
+    /// Pushes `value` with `key` in O(1). Panics if `key` is smaller than `last_key`.
+    pub fn push(&mut self, key: K, value: V) {
+        assert!(
+            key >= self.last,
+            "radix heap keys must not be smaller than the last popped key"
+        );
+        self.buckets[key.radix_distance(self.last) as usize].push((key, value));
+        self.len += 1;
+    }
+
+    /// The smallest key, without redistributing any bucket.
+    pub fn peek_key(&self) -> Option<K> {
+        let bucket = self.buckets.iter().find(|bucket| !bucket.is_empty())?;
+        bucket.iter().map(|(key, _)| *key).min()
+    }
+
+    /// Pops an entry with the smallest key; entries with equal keys pop in arbitrary order.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        if self.buckets[0].is_empty() {
+            let index = self.buckets.iter().position(|bucket| !bucket.is_empty())?;
+            let mut bucket = mem::take(&mut self.buckets[index]);
+            self.last = bucket
+                .iter()
+                .map(|(key, _)| *key)
+                .min()
+                .expect("bucket is not empty");
+            // 以新的 last 为基准，桶里的元素都会落到更低的桶
+            for (key, value) in bucket.drain(..) {
+                self.buckets[key.radix_distance(self.last) as usize].push((key, value));
+            }
+            // 留着这个桶已分配的容量
+            self.buckets[index] = bucket;
+        }
+        let entry = self.buckets[0].pop()?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    /// Removes all entries and resets `last_key` to zero.
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+        self.last = K::ZERO;
+        self.len = 0;
+    }
+}
+
+impl<K: RadixKey, V> Default for RadixHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::radix_heap::RadixHeap;
+    use rand::{thread_rng, Rng};
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut heap = RadixHeap::new();
+        for key in [5u32, 1, 9, 3, 3, 7] {
+            heap.push(key, key * 10);
+        }
+        assert_eq!(heap.len(), 6);
+        assert_eq!(heap.peek_key(), Some(1));
+        assert_eq!(heap.pop(), Some((1, 10)));
+        heap.push(2, 20);
+        let mut popped = Vec::new();
+        while let Some((key, value)) = heap.pop() {
+            assert_eq!(value, key * 10);
+            popped.push(key);
+        }
+        assert_eq!(popped, [2, 3, 3, 5, 7, 9]);
+        assert_eq!(heap.last_key(), 9);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be smaller")]
+    fn test_rejects_non_monotone_key() {
+        let mut heap = RadixHeap::new();
+        heap.push(5u64, ());
+        heap.pop();
+        heap.push(4, ());
+    }
+
+    #[test]
+    fn test_extreme_keys() {
+        let mut heap = RadixHeap::new();
+        for key in [u8::MAX, 0, 128, 127, u8::MAX] {
+            heap.push(key, ());
+        }
+        let popped: Vec<_> = std::iter::from_fn(|| heap.pop().map(|(key, _)| key)).collect();
+        assert_eq!(popped, [0, 127, 128, u8::MAX, u8::MAX]);
+        heap.clear();
+        heap.push(0, ());
+        assert_eq!(heap.pop(), Some((0, ())));
+    }
+
+    // 单调操作序列：新键不小于上次弹出的键，与 std 的堆对比
+    #[test]
+    fn test_monotone_against_std() {
+        let mut rng = thread_rng();
+        let mut heap = RadixHeap::<u64, ()>::new();
+        let mut std_heap = BinaryHeap::new();
+        for _ in 0..100_000 {
+            if rng.gen_bool(0.55) {
+                let key = heap.last_key() + rng.gen_range(0..1_000u64);
+                heap.push(key, ());
+                std_heap.push(Reverse(key));
+            } else {
+                let key = heap.pop().map(|(key, _)| key);
+                assert_eq!(key, std_heap.pop().map(|key| key.0));
+            }
+            assert_eq!(heap.len(), std_heap.len());
+            assert_eq!(heap.peek_key(), std_heap.peek().map(|key| key.0));
+        }
+    }
+}
@@ -0,0 +1,303 @@
+// 分代轮转的布隆过滤器：在滑动时间窗口内去重。
+//
+// 保留 N 代 BloomFilter，新元素只写入最新一代，查询时检查所有存活的代。每到一个轮转点
+// （经过固定时长，或最新一代写满固定条数）就淘汰最老的一代、开一个空的新一代，
+// 所以一个元素至少被记住 N − 1 个周期，至多 N 个周期，过滤器也不会被写满。
+//
+// 时间由 Clock 提供，测试中可以换成手动拨动的假时钟。
+
+use crate::bloom_filter::BloomFilter;
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Time elapsed since some fixed origin.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// Rotate every time the clock advances by this much.
+    Every(Duration),
+    /// Rotate once the newest generation has taken this many inserts.
+    Items(usize),
+    /// Only rotate when `rotate` is called.
+    Manual,
+}
+
+#[allow(unused)]
+pub struct RotatingBloomFilter<T: ?Sized, C = SystemClock> {
+    // 队首最老，队尾最新
+    generations: VecDeque<BloomFilter<T>>,
+    generation_count: usize,
+    cap: usize,
+    err_rate: f64,
+    rotation: Rotation,
+    clock: C,
+    last_rotation: Duration,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> RotatingBloomFilter<T> {
+    /// Keeps `generation_count` generations, each sized for `cap` values at `err_rate`.
+    pub fn new(generation_count: usize, cap: usize, err_rate: f64, rotation: Rotation) -> Self {
+        Self::with_clock(
+            generation_count,
+            cap,
+            err_rate,
+            rotation,
+            SystemClock::default(),
+        )
+    }
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash, C: Clock> RotatingBloomFilter<T, C> {
+    pub fn with_clock(
+        generation_count: usize,
+        cap: usize,
+        err_rate: f64,
+        rotation: Rotation,
+        clock: C,
+    ) -> Self {
+        assert!(generation_count > 0, "generation_count must be positive");
+        match rotation {
+            Rotation::Every(period) => assert!(!period.is_zero(), "period must be positive"),
+            Rotation::Items(items) => assert!(items > 0, "items must be positive"),
+            Rotation::Manual => {}
+        }
+        let last_rotation = clock.now();
+        let mut generations = VecDeque::with_capacity(generation_count);
+        generations.push_back(BloomFilter::new(cap, err_rate));
+        RotatingBloomFilter {
+            generations,
+            generation_count,
+            cap,
+            err_rate,
+            rotation,
+            clock,
+            last_rotation,
+        }
+    }
+
+    /// 距离上次轮转已经错过了几个轮转点
+    fn due_rotations(&self) -> usize {
+        match self.rotation {
+            Rotation::Every(period) => {
+                let elapsed = self.clock.now().saturating_sub(self.last_rotation);
+                (elapsed.as_nanos() / period.as_nanos()) as usize
+            }
+            Rotation::Items(items) => self.newest().len() / items,
+            Rotation::Manual => 0,
+        }
+    }
+
+    fn newest(&self) -> &BloomFilter<T> {
+        self.generations.back().expect("at least one generation")
+    }
+
+    /// Retires the oldest generation once all `generation_count` are live, and
+    /// starts a new, empty one.
+    pub fn rotate(&mut self) {
+        let generation = if self.generations.len() == self.generation_count {
+            let mut oldest = self
+                .generations
+                .pop_front()
+                .expect("at least one generation");
+            oldest.clear();
+            oldest
+        } else {
+            BloomFilter::new(self.cap, self.err_rate)
+        };
+        self.generations.push_back(generation);
+    }
+
+    fn advance(&mut self) {
+        let due = self.due_rotations();
+        if let Rotation::Every(period) = self.rotation {
+            // 轮转点对齐到 last_rotation + i·period，不随插入时间漂移
+            let now = self.clock.now();
+            let elapsed = now.saturating_sub(self.last_rotation);
+            let remainder = elapsed.as_nanos() % period.as_nanos();
+            self.last_rotation = now - Duration::from_nanos(remainder as u64);
+        }
+        // 错过的轮转点超过代数时，所有代都已过期，多转几次没有意义
+        for _ in 0..due.min(self.generation_count) {
+            self.rotate();
+        }
+    }
+
+    pub fn insert(&mut self, value: &T) {
+        self.advance();
+        self.generations
+            .back_mut()
+            .expect("at least one generation")
+            .insert(value);
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        // 时间已经过去但还没来得及轮转掉的代不参与查询；按条数轮转时，
+        // 写满的最新一代要等下一次插入才轮转，在那之前最老的一代仍然有效
+        let expired = match self.rotation {
+            Rotation::Every(_) => self.due_rotations(),
+            _ => 0,
+        };
+        let live = self.generations.len();
+        let skip = (live + expired)
+            .saturating_sub(self.generation_count)
+            .min(live);
+        self.generations
+            .iter()
+            .skip(skip)
+            .any(|generation| generation.contains(value))
+    }
+
+    /// Number of generations currently allocated, at most `generation_count`.
+    pub fn live_generations(&self) -> usize {
+        self.generations.len()
+    }
+
+    pub fn generation_count(&self) -> usize {
+        self.generation_count
+    }
+
+    /// Number of inserts held by the live generations.
+    pub fn len(&self) -> usize {
+        self.generations
+            .iter()
+            .map(|generation| generation.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rotating_bloom_filter::{Clock, RotatingBloomFilter, Rotation};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<Duration>>);
+
+    impl FakeClock {
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_time_window() {
+        let clock = FakeClock::default();
+        let mut filter =
+            RotatingBloomFilter::with_clock(3, 1_000, 0.01, Rotation::Every(MINUTE), clock.clone());
+        filter.insert("a");
+        clock.advance(MINUTE);
+        filter.insert("b");
+        assert_eq!(filter.live_generations(), 2);
+        clock.advance(MINUTE);
+        filter.insert("c");
+        assert_eq!(filter.live_generations(), 3);
+        assert!(filter.contains("a") && filter.contains("b") && filter.contains("c"));
+
+        // 第三个周期结束，"a" 所在的代过期；没有插入触发轮转，查询也要看到这一点
+        clock.advance(MINUTE);
+        assert!(!filter.contains("a"));
+        assert!(filter.contains("b") && filter.contains("c"));
+        filter.insert("d");
+        assert_eq!(filter.live_generations(), 3);
+        assert!(!filter.contains("a"));
+
+        clock.advance(MINUTE * 2);
+        assert!(!filter.contains("b") && !filter.contains("c"));
+        assert!(filter.contains("d"));
+    }
+
+    #[test]
+    fn test_long_idle_expires_everything() {
+        let clock = FakeClock::default();
+        let mut filter =
+            RotatingBloomFilter::with_clock(4, 1_000, 0.01, Rotation::Every(MINUTE), clock.clone());
+        for key in 0..100u64 {
+            filter.insert(&key);
+            clock.advance(Duration::from_secs(1));
+        }
+        clock.advance(MINUTE * 1_000);
+        assert!((0..100u64).all(|key| !filter.contains(&key)));
+        filter.insert(&1_000);
+        assert!(filter.contains(&1_000));
+        assert_eq!(filter.len(), 1);
+
+        // 轮转点对齐到固定周期
+        clock.advance(Duration::from_secs(59));
+        filter.insert(&1_001);
+        clock.advance(Duration::from_secs(1));
+        assert!(filter.contains(&1_000));
+        clock.advance(MINUTE * 3);
+        assert!(!filter.contains(&1_000));
+    }
+
+    #[test]
+    fn test_item_count_rotation() {
+        let mut filter = RotatingBloomFilter::new(2, 100, 0.01, Rotation::Items(100));
+        for key in 0..100u64 {
+            filter.insert(&key);
+        }
+        assert_eq!(filter.live_generations(), 1);
+        for key in 100..200u64 {
+            filter.insert(&key);
+        }
+        assert_eq!(filter.live_generations(), 2);
+        assert!((0..200u64).all(|key| filter.contains(&key)));
+        filter.insert(&200);
+        assert_eq!(filter.live_generations(), 2);
+        assert!((100..=200u64).all(|key| filter.contains(&key)));
+        let remembered = (0..100u64).filter(|key| filter.contains(key)).count();
+        assert!(remembered < 10);
+    }
+
+    #[test]
+    fn test_manual_rotation() {
+        let mut filter = RotatingBloomFilter::<str>::new(2, 100, 0.01, Rotation::Manual);
+        filter.insert("old");
+        filter.rotate();
+        filter.insert("new");
+        assert!(filter.contains("old"));
+        filter.rotate();
+        assert!(!filter.contains("old"));
+        assert!(filter.contains("new"));
+        assert_eq!(filter.len(), 1);
+    }
+}
@@ -0,0 +1,324 @@
+// 滑动窗口中位数 / 分位数：用两个堆维护一个动态集合的第 r 小元素。
+//
+// low 是大顶堆，存最小的 r 个元素；high 是小顶堆，存其余元素。low 的堆顶就是第 r 小的元素。
+// 分位数取 nearest-rank 定义：n 个元素的 q 分位数是第 max(1, ⌈q·n⌉) 小的元素，
+// 中位数 q = 0.5 即下中位数。每次插入或删除后，在两个堆之间挪动堆顶元素使 low 恰好有 r 个。
+//
+// 删除用惰性删除：堆不支持删除任意元素，只在对应一侧的待删表中记一笔，等它浮到堆顶时再
+// 真正弹出。两侧各用一张待删表，这样相同的值分别落在两侧时也不会记错。待删元素过多时
+// 整体重建一次，使内存保持在 O(窗口大小)。
+
+use crate::binary_heap::{BinaryHeap, Compare, MaxOrder, MinOrder};
+use std::collections::BTreeMap;
+
+// 堆里元素的个数超过有效个数的两倍（再加一点余量）时重建
+const COMPACT_SLACK: usize = 32;
+
+struct LazyHeap<T, C> {
+    heap: BinaryHeap<T, C>,
+    pending: BTreeMap<T, usize>,
+    len: usize,
+}
+
+impl<T: Ord + Clone, C: Compare<T>> LazyHeap<T, C> {
+    fn new(cmp: C) -> Self {
+        LazyHeap {
+            heap: BinaryHeap::with_comparator(cmp),
+            pending: BTreeMap::new(),
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, val: T) {
+        self.heap.push(val);
+        self.len += 1;
+    }
+
+    // 堆顶若已被删除就弹掉，保证 peek 看到的是有效元素
+    fn prune(&mut self) {
+        while let Some(top) = self.heap.peek() {
+            match self.pending.get_mut(top) {
+                Some(count) => {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.pending.remove(top);
+                    }
+                    self.heap.pop();
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let top = self.heap.pop()?;
+        self.len -= 1;
+        self.prune();
+        Some(top)
+    }
+
+    fn remove(&mut self, val: &T) {
+        *self.pending.entry(val.clone()).or_insert(0) += 1;
+        self.len -= 1;
+        self.prune();
+        if self.heap.len() > 2 * self.len + COMPACT_SLACK {
+            let pending = &mut self.pending;
+            self.heap.retain(|val| match pending.get_mut(val) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            });
+            pending.clear();
+        }
+    }
+}
+
+#[allow(unused)]
+pub struct RunningQuantile<T> {
+    // 最小的 rank 个元素
+    low: LazyHeap<T, MaxOrder>,
+    // 其余元素
+    high: LazyHeap<T, MinOrder>,
+    quantile: f64,
+}
+
+#[allow(unused)]
+impl<T: Ord + Clone> RunningQuantile<T> {
+    /// Tracks the `quantile` (in [0, 1]) of the inserted values by nearest rank.
+    pub fn new(quantile: f64) -> Self {
+        assert!(
+            (0f64..=1f64).contains(&quantile),
+            "quantile must be in [0, 1], got {quantile}"
+        );
+        RunningQuantile {
+            low: LazyHeap::new(MaxOrder),
+            high: LazyHeap::new(MinOrder),
+            quantile,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.low.len + self.high.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn quantile(&self) -> f64 {
+        self.quantile
+    }
+
+    // 第 rank 小的元素即为所求，rank = max(1, ⌈q·n⌉)，即满足 rank / n >= q 的最小整数。
+    // 浮点乘法会差一：0.07 * 100 = 7.000000000000001，向上取整成了 8。
+    // 除法是正确舍入且单调的，用 rank / n 与 q 比较校正一次。
+    fn rank(&self) -> usize {
+        let len = self.len();
+        let mut rank = (self.quantile * len as f64).ceil() as usize;
+        if rank > 0 && (rank - 1) as f64 / len as f64 >= self.quantile {
+            rank -= 1;
+        } else if rank < len && (rank as f64 / len as f64) < self.quantile {
+            rank += 1;
+        }
+        rank.clamp(len.min(1), len)
+    }
+
+    fn rebalance(&mut self) {
+        let rank = self.rank();
+        while self.low.len > rank {
+            let val = self.low.pop().expect("low holds more than rank values");
+            self.high.push(val);
+        }
+        while self.low.len < rank {
+            let val = self.high.pop().expect("high holds the remaining values");
+            self.low.push(val);
+        }
+    }
+
+    pub fn insert(&mut self, val: T) {
+        if self.low.peek().is_none_or(|top| val <= *top) {
+            self.low.push(val);
+        } else {
+            self.high.push(val);
+        }
+        self.rebalance();
+    }
+
+    /// Removes one occurrence of `val`, which must have been inserted and not yet removed.
+    pub fn remove(&mut self, val: &T) {
+        // low 中都不大于它的堆顶，high 中都不小于，相等时两边都行
+        if self.low.peek().is_some_and(|top| val <= top) {
+            self.low.remove(val);
+        } else {
+            self.high.remove(val);
+        }
+        self.rebalance();
+    }
+
+    /// The nearest-rank quantile of the current values.
+    pub fn get(&self) -> Option<&T> {
+        self.low.peek()
+    }
+}
+
+#[allow(unused)]
+pub struct RunningMedian<T> {
+    inner: RunningQuantile<T>,
+}
+
+#[allow(unused)]
+impl<T: Ord + Clone> RunningMedian<T> {
+    pub fn new() -> Self {
+        RunningMedian {
+            inner: RunningQuantile::new(0.5),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn insert(&mut self, val: T) {
+        self.inner.insert(val);
+    }
+
+    /// Removes one occurrence of `val`, which must have been inserted and not yet removed.
+    pub fn remove(&mut self, val: &T) {
+        self.inner.remove(val);
+    }
+
+    /// The lower median: the ⌈n/2⌉-th smallest value.
+    pub fn median(&self) -> Option<&T> {
+        self.inner.get()
+    }
+}
+
+impl<T: Ord + Clone> Default for RunningMedian<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::running_median::{RunningMedian, RunningQuantile, COMPACT_SLACK};
+    use rand::{thread_rng, Rng};
+    use std::collections::VecDeque;
+
+    // 分位数用分数 num / den 表示，按整数算 ⌈num·n / den⌉，不经过浮点
+    fn nearest_rank(sorted: &[u32], (num, den): (usize, usize)) -> Option<&u32> {
+        let len = sorted.len();
+        let rank = (num * len).div_ceil(den).clamp(len.min(1), len);
+        rank.checked_sub(1).map(|index| &sorted[index])
+    }
+
+    fn tracker((num, den): (usize, usize)) -> RunningQuantile<u32> {
+        RunningQuantile::new(num as f64 / den as f64)
+    }
+
+    fn insert_sorted(sorted: &mut Vec<u32>, val: u32) {
+        let index = sorted.partition_point(|other| *other < val);
+        sorted.insert(index, val);
+    }
+
+    #[test]
+    fn test_median() {
+        let mut median = RunningMedian::new();
+        assert_eq!(median.median(), None);
+        for (val, expected) in [(5, 5), (1, 1), (3, 3), (9, 3), (7, 5)] {
+            median.insert(val);
+            assert_eq!(median.median(), Some(&expected));
+        }
+        median.remove(&5);
+        assert_eq!(median.median(), Some(&3));
+        median.remove(&1);
+        median.remove(&3);
+        assert_eq!(median.median(), Some(&7));
+        assert_eq!(median.len(), 2);
+    }
+
+    #[test]
+    fn test_stream_against_sorted_vec() {
+        let mut rng = thread_rng();
+        let quantiles = [
+            (0, 1),
+            (7, 100),
+            (1, 10),
+            (1, 4),
+            (1, 2),
+            (9, 10),
+            (99, 100),
+            (1, 1),
+        ];
+        let mut trackers: Vec<_> = quantiles.iter().map(|q| tracker(*q)).collect();
+        let mut sorted = Vec::new();
+        for _ in 0..5_000 {
+            let val = rng.gen_range(0..1_000);
+            insert_sorted(&mut sorted, val);
+            for (tracker, quantile) in trackers.iter_mut().zip(quantiles) {
+                tracker.insert(val);
+                assert_eq!(tracker.get(), nearest_rank(&sorted, quantile));
+            }
+        }
+    }
+
+    // q·n 恰好是整数时，浮点乘法的误差不能让 rank 多一或少一，比如 0.07 * 100 = 7.000000000000001
+    #[test]
+    fn test_percentiles_of_exact_ranks() {
+        for len in [100, 1_000] {
+            for percent in 0..=100 {
+                let mut quantile = tracker((percent, 100));
+                for val in (1..=len as u32).rev() {
+                    quantile.insert(val);
+                }
+                let expected = (percent * len / 100).max(1) as u32;
+                assert_eq!(quantile.get(), Some(&expected), "q = {percent}%, n = {len}");
+            }
+        }
+    }
+
+    // 滑动窗口：窗口满了就删掉最早的元素，取值范围小，重复值很多
+    #[test]
+    fn test_sliding_window_against_sorted_vec() {
+        let mut rng = thread_rng();
+        for (window, max) in [(1, 10), (10, 5), (101, 50), (1_000, 100_000)] {
+            let mut median = RunningMedian::new();
+            let mut p90 = tracker((9, 10));
+            let mut values = VecDeque::new();
+            let mut sorted = Vec::new();
+            for _ in 0..10_000 {
+                if values.len() == window {
+                    let old = values.pop_front().unwrap();
+                    median.remove(&old);
+                    p90.remove(&old);
+                    let index = sorted.binary_search(&old).unwrap();
+                    sorted.remove(index);
+                }
+                let val = rng.gen_range(0..max);
+                values.push_back(val);
+                median.insert(val);
+                p90.insert(val);
+                insert_sorted(&mut sorted, val);
+                assert_eq!(median.len(), sorted.len());
+                assert_eq!(median.median(), nearest_rank(&sorted, (1, 2)));
+                assert_eq!(p90.get(), nearest_rank(&sorted, (9, 10)));
+            }
+            // 惰性删除的元素会被及时清理
+            let stored = median.inner.low.heap.len() + median.inner.high.heap.len();
+            assert!(
+                stored <= 2 * (window + COMPACT_SLACK + 1),
+                "{stored} values stored"
+            );
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// 可扩容布隆过滤器（Almeida 等，Scalable Bloom Filters）。
+//
+// 当前阶段插满容量后，新开一个容量乘以 s、误差率乘以 r 的 BloomFilter。第 i 个阶段的误差率为
+// P0·r^i，总误差率上限为 Σ P0·r^i = P0 / (1 − r)，取 P0 = ε(1 − r) 即可保证无论插入多少
+// 元素，整体误差率都不超过 ε。
+
+use crate::bloom_filter::BloomFilter;
+use std::hash::Hash;
+
+const DEFAULT_GROWTH_FACTOR: usize = 2;
+const DEFAULT_TIGHTENING_RATIO: f64 = 0.85;
+
+struct Stage<T: ?Sized> {
+    filter: BloomFilter<T>,
+    cap: usize,
+    err_rate: f64,
+}
+
+#[allow(unused)]
+pub struct ScalableBloomFilter<T: ?Sized> {
+    stages: Vec<Stage<T>>,
+    initial_cap: usize,
+    err_rate: f64,
+    growth_factor: usize,
+    tightening_ratio: f64,
+    len: usize,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash> ScalableBloomFilter<T> {
+    pub fn new(initial_cap: usize, err_rate: f64) -> Self {
+        Self::with_params(
+            initial_cap,
+            err_rate,
+            DEFAULT_GROWTH_FACTOR,
+            DEFAULT_TIGHTENING_RATIO,
+        )
+    }
+
+    pub fn with_params(
+        initial_cap: usize,
+        err_rate: f64,
+        growth_factor: usize,
+        tightening_ratio: f64,
+    ) -> Self {
+        assert!(initial_cap > 0, "initial_cap must be positive");
+        assert!(growth_factor > 0, "growth_factor must be positive");
+        assert!(
+            tightening_ratio > 0f64 && tightening_ratio < 1f64,
+            "tightening_ratio must be in (0, 1), got {tightening_ratio}"
+        );
+        let mut filter = ScalableBloomFilter {
+            stages: Vec::new(),
+            initial_cap,
+            err_rate,
+            growth_factor,
+            tightening_ratio,
+            len: 0,
+        };
+        filter.add_stage();
+        filter
+    }
+
+    fn add_stage(&mut self) {
+        let (cap, err_rate) = match self.stages.last() {
+            None => (
+                self.initial_cap,
+                self.err_rate * (1f64 - self.tightening_ratio),
+            ),
+            Some(last) => (
+                last.cap.saturating_mul(self.growth_factor),
+                last.err_rate * self.tightening_ratio,
+            ),
+        };
+        self.stages.push(Stage {
+            filter: BloomFilter::new(cap, err_rate),
+            cap,
+            err_rate,
+        });
+    }
+
+    /// Inserts `value`, returning `false` if it was (probably) already present.
+    pub fn insert(&mut self, value: &T) -> bool {
+        if self.contains(value) {
+            return false;
+        }
+        if self
+            .stages
+            .last()
+            .is_some_and(|stage| stage.filter.len() >= stage.cap)
+        {
+            self.add_stage();
+        }
+        let stage = self.stages.last_mut().expect("at least one stage");
+        stage.filter.insert(value);
+        self.len += 1;
+        true
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.stages.iter().any(|stage| stage.filter.contains(value))
+    }
+
+    /// Number of distinct values inserted, up to false positives.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of `BloomFilter` stages allocated so far.
+    pub fn stage_count(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Total number of values the allocated stages are sized for.
+    pub fn capacity(&self) -> usize {
+        self.stages.iter().map(|stage| stage.cap).sum()
+    }
+
+    /// The configured compound error rate, which holds for any number of stages.
+    pub fn err_rate(&self) -> f64 {
+        self.err_rate
+    }
+
+    /// Upper bound on the false positive rate of the allocated stages: Σ P0·r^i
+    pub fn false_positive_rate_bound(&self) -> f64 {
+        self.stages.iter().map(|stage| stage.err_rate).sum()
+    }
+
+    /// Expected false positive rate at the current fill level: 1 − Π(1 − Pi)
+    pub fn expected_false_positive_rate(&self) -> f64 {
+        1f64 - self
+            .stages
+            .iter()
+            .map(|stage| 1f64 - stage.filter.expected_false_positive_rate())
+            .product::<f64>()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|stage| stage.filter.size_in_bytes())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scalable_bloom_filter::ScalableBloomFilter;
+    use rand::random;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_grows_past_initial_capacity() {
+        let mut filter = ScalableBloomFilter::new(1_000, 0.01);
+        assert_eq!(filter.stage_count(), 1);
+        // 误判为已存在的元素不会被插入，所以按 len 而不是按插入次数来数
+        let mut key = 0u64;
+        while filter.len() < 1_000 {
+            filter.insert(&key);
+            key += 1;
+        }
+        assert_eq!(filter.stage_count(), 1);
+        while filter.len() == 1_000 {
+            filter.insert(&key);
+            key += 1;
+        }
+        assert_eq!(filter.stage_count(), 2);
+        assert_eq!(filter.capacity(), 3_000);
+        assert!(!filter.insert(&0u64));
+        assert!((0..key).all(|key| filter.contains(&key)));
+    }
+
+    #[test]
+    fn test_false_positive_rate_holds() {
+        let err_rate = 0.01;
+        let cap = 200_000;
+        let mut filter = ScalableBloomFilter::new(1_000, err_rate);
+        let mut inserted = HashSet::with_capacity(cap);
+        while inserted.len() < cap {
+            let key: u64 = random();
+            if inserted.insert(key) {
+                filter.insert(&key);
+            }
+        }
+        assert!(inserted.iter().all(|key| filter.contains(key)));
+        // 1000·(2^8 − 1) ≥ 200000，需要 8 个阶段
+        assert_eq!(filter.stage_count(), 8);
+        assert!(filter.false_positive_rate_bound() < err_rate);
+        assert!(filter.expected_false_positive_rate() < err_rate);
+
+        let probes = 200_000;
+        let false_positives = (0..probes)
+            .map(|_| random::<u64>())
+            .filter(|key| !inserted.contains(key) && filter.contains(key))
+            .count();
+        let measured = false_positives as f64 / probes as f64;
+        assert!(
+            measured < err_rate,
+            "{} stages: measured fpr {measured}, expected {}",
+            filter.stage_count(),
+            filter.expected_false_positive_rate()
+        );
+    }
+}
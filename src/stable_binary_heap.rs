@@ -0,0 +1,186 @@
+// 稳定的二叉堆：优先级相同的元素按插入顺序（先进先出）出堆。
+//
+// 堆本身不稳定，相等元素的出堆顺序取决于它们在数组里被交换到的位置。这里给每个元素附上
+// 一个递增的序号，比较器先按元素比较，相等时序号小的先出，这样相等元素之间就有了确定的
+// 全序。代价是每个元素多 8 字节。
+
+use crate::binary_heap::{BinaryHeap, Compare, FnOrder, KeyOrder, MaxOrder, MinOrder};
+use std::cmp::Ordering;
+
+struct Stamped<T> {
+    val: T,
+    seq: u64,
+}
+
+struct StableOrder<C>(C);
+
+impl<T, C: Compare<T>> Compare<Stamped<T>> for StableOrder<C> {
+    fn compare(&self, a: &Stamped<T>, b: &Stamped<T>) -> Ordering {
+        self.0.compare(&a.val, &b.val).then(a.seq.cmp(&b.seq))
+    }
+}
+
+#[allow(unused)]
+pub struct StableBinaryHeap<T, C = MinOrder> {
+    heap: BinaryHeap<Stamped<T>, StableOrder<C>>,
+    next_seq: u64,
+}
+
+#[allow(unused)]
+impl<T: Ord> StableBinaryHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T: Ord> StableBinaryHeap<T, MaxOrder> {
+    pub fn new_max() -> Self {
+        Self::with_comparator(MaxOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T, F: Fn(&T, &T) -> Ordering> StableBinaryHeap<T, FnOrder<F>> {
+    /// Heap ordered by `cmp`, where `Less` means the first argument pops first.
+    pub fn by(cmp: F) -> Self {
+        Self::with_comparator(FnOrder(cmp))
+    }
+}
+
+#[allow(unused)]
+impl<T, K: Ord, F: Fn(&T) -> K> StableBinaryHeap<T, KeyOrder<F>> {
+    /// Heap that pops the element with the smallest `key` first.
+    pub fn by_key(key: F) -> Self {
+        Self::with_comparator(KeyOrder(key))
+    }
+}
+
+#[allow(unused)]
+impl<T, C: Compare<T>> StableBinaryHeap<T, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        StableBinaryHeap {
+            heap: BinaryHeap::with_comparator(StableOrder(cmp)),
+            next_seq: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn push(&mut self, val: T) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(Stamped { val, seq });
+    }
+
+    /// Pops the first element by `C`; among equal ones, the one pushed earliest.
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|stamped| stamped.val)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|stamped| &stamped.val)
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    /// Consumes the heap and returns its elements in the order `pop` would return them.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|stamped| stamped.val)
+            .collect()
+    }
+}
+
+impl<T: Ord> Default for StableBinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for StableBinaryHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for StableBinaryHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::stable_binary_heap::StableBinaryHeap;
+    use rand::{thread_rng, Rng};
+
+    #[derive(Debug, PartialEq)]
+    struct Job {
+        priority: u32,
+        id: usize,
+    }
+
+    #[test]
+    fn test_equal_keys_pop_in_insertion_order() {
+        let mut rng = thread_rng();
+        let mut heap = StableBinaryHeap::by_key(|job: &Job| job.priority);
+        for id in 0..5_000 {
+            heap.push(Job {
+                priority: rng.gen_range(0..3),
+                id,
+            });
+        }
+        let mut popped = Vec::new();
+        while let Some(job) = heap.pop() {
+            popped.push((job.priority, job.id));
+        }
+        let mut expected = popped.clone();
+        expected.sort();
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn test_all_equal_is_fifo() {
+        let mut heap = StableBinaryHeap::by_key(|_: &usize| 0);
+        heap.extend(0..3_000);
+        assert_eq!(heap.peek(), Some(&0));
+        // 边出边进，仍然先进先出
+        for id in 3_000..6_000 {
+            assert_eq!(heap.pop(), Some(id - 3_000));
+            heap.push(id);
+        }
+        assert_eq!(heap.into_sorted_vec(), (3_000..6_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_max_order_is_stable() {
+        let mut heap = StableBinaryHeap::by(|a: &(u32, char), b: &(u32, char)| b.0.cmp(&a.0));
+        for item in [(1, 'a'), (2, 'b'), (1, 'c'), (2, 'd'), (3, 'e'), (1, 'f')] {
+            heap.push(item);
+        }
+        let order: String = heap.into_sorted_vec().iter().map(|item| item.1).collect();
+        assert_eq!(order, "ebdacf");
+
+        let heap: StableBinaryHeap<_> = [3, 1, 2].into_iter().collect();
+        assert_eq!(heap.into_sorted_vec(), [1, 2, 3]);
+        let mut heap = StableBinaryHeap::new_max();
+        heap.extend([3, 1, 2]);
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.pop(), Some(3));
+    }
+}
@@ -0,0 +1,177 @@
+// 流式 Top-K：只用 O(k) 内存保留目前见过的最大（或最小）的 k 个元素。
+//
+// 求最大的 k 个时维护一个大小为 k 的小顶堆，堆顶是已保留元素中最小的一个，也就是进入
+// Top-K 的门槛。新元素不超过门槛直接丢弃，否则替换堆顶再下沉，每个元素 O(log k)。
+// 求最小的 k 个时反过来用大顶堆。
+
+use crate::binary_heap::{BinaryHeap, Compare, MaxOrder, MinOrder};
+
+// k 可能远大于实际的元素个数（比如用 usize::MAX 表示不限），只预分配这么多，其余按需增长
+const MAX_PREALLOCATED: usize = 1024;
+
+#[allow(unused)]
+pub struct TopK<T, C = MinOrder> {
+    // 堆顶是已保留元素中最先被淘汰的一个
+    heap: BinaryHeap<T, C>,
+    k: usize,
+}
+
+#[allow(unused)]
+impl<T: Ord> TopK<T> {
+    /// Keeps the `k` largest elements.
+    pub fn largest(k: usize) -> Self {
+        Self::with_comparator(k, MinOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T: Ord> TopK<T, MaxOrder> {
+    /// Keeps the `k` smallest elements.
+    pub fn smallest(k: usize) -> Self {
+        Self::with_comparator(k, MaxOrder)
+    }
+}
+
+#[allow(unused)]
+impl<T, C: Compare<T>> TopK<T, C> {
+    /// Keeps the `k` elements that `cmp` orders last, i.e. evicts what a heap ordered by
+    /// `cmp` would pop first.
+    pub fn with_comparator(k: usize, cmp: C) -> Self {
+        TopK {
+            heap: BinaryHeap::with_capacity_and_comparator(k.min(MAX_PREALLOCATED), cmp),
+            k,
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// The kept element that the next qualifying push would evict.
+    pub fn threshold(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    /// Offers `val`, returning the element that didn't make the cut: the evicted one,
+    /// `val` itself, or `None` while fewer than `k` elements are kept.
+    pub fn push(&mut self, val: T) -> Option<T> {
+        if self.heap.len() < self.k {
+            self.heap.push(val);
+            return None;
+        }
+        // 比门槛更靠后才能进入 Top-K，相等时保留先来的
+        let qualifies = self
+            .heap
+            .peek()
+            .is_some_and(|top| self.heap.comparator().compare(top, &val).is_lt());
+        if !qualifies {
+            return Some(val);
+        }
+        let mut top = self
+            .heap
+            .peek_mut()
+            .expect("qualifying push has a threshold");
+        Some(std::mem::replace(&mut *top, val))
+    }
+
+    /// Iterates over the kept elements in arbitrary order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.heap.iter()
+    }
+
+    /// The kept elements, best first: largest first for `largest`, smallest first for
+    /// `smallest`.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut sorted = self.heap.into_sorted_vec();
+        sorted.reverse();
+        sorted
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for TopK<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary_heap::KeyOrder;
+    use crate::top_k::{TopK, MAX_PREALLOCATED};
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_largest_and_smallest() {
+        let mut largest = TopK::largest(3);
+        let mut smallest = TopK::smallest(3);
+        for val in [5, 1, 9, 3, 7, 2, 8] {
+            largest.push(val);
+            smallest.push(val);
+        }
+        assert_eq!(largest.len(), 3);
+        assert_eq!(largest.threshold(), Some(&7));
+        assert_eq!(largest.into_sorted_vec(), [9, 8, 7]);
+        assert_eq!(smallest.threshold(), Some(&3));
+        assert_eq!(smallest.into_sorted_vec(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_push_returns_rejected() {
+        let mut top = TopK::largest(2);
+        assert_eq!(top.push(4), None);
+        assert_eq!(top.push(6), None);
+        assert_eq!(top.push(1), Some(1));
+        assert_eq!(top.push(5), Some(4));
+        assert_eq!(top.push(5), Some(5));
+        let mut empty = TopK::largest(0);
+        assert_eq!(empty.push(1), Some(1));
+        assert!(empty.is_empty());
+    }
+
+    // k 很大时不会按 k 预分配
+    #[test]
+    fn test_huge_k() {
+        let mut top = TopK::largest(usize::MAX);
+        assert!(top.heap.capacity() <= MAX_PREALLOCATED);
+        top.extend([3, 1, 2]);
+        assert_eq!(top.into_sorted_vec(), [3, 2, 1]);
+    }
+
+    #[test]
+    fn test_comparator() {
+        // 保留最长的两个单词，长度相同时保留先出现的
+        let mut top = TopK::with_comparator(2, KeyOrder(|word: &&str| word.len()));
+        top.extend(["fig", "pear", "banana", "kiwi", "apple"]);
+        assert_eq!(top.into_sorted_vec(), ["banana", "apple"]);
+    }
+
+    #[test]
+    fn test_million_items() {
+        let mut rng = thread_rng();
+        let stream: Vec<u64> = (0..2_000_000)
+            .map(|_| rng.gen_range(0..1_000_000))
+            .collect();
+        let mut sorted = stream.clone();
+        sorted.sort_unstable();
+        for k in [1, 10, 1_000, 100_000] {
+            let mut largest = TopK::largest(k);
+            let mut smallest = TopK::smallest(k);
+            largest.extend(stream.iter().copied());
+            smallest.extend(stream.iter().copied());
+            assert_eq!(largest.len(), k);
+            let expected: Vec<_> = sorted.iter().rev().take(k).copied().collect();
+            assert_eq!(largest.into_sorted_vec(), expected);
+            assert_eq!(smallest.into_sorted_vec(), sorted[..k]);
+        }
+    }
+}
@@ -0,0 +1,416 @@
+// XOR 过滤器（Graf & Lemire，Xor Filters: Faster and Smaller Than Bloom and Cuckoo Filters）。
+//
+// 只能从一组已知的 key 一次性构建，之后不能再插入。数组分成三段，每个 key 在每段各选一个位置，
+// 构建时保证三个位置上的指纹异或起来等于 key 的指纹：
+// fingerprint(x) = B[h0(x)] ⊕ B[h1(x)] ⊕ B[h2(x)]
+// 数组长度取 1.23n + 32，每个 key 约占 1.23·f 位，误差率为 2^−f，比同误差率的布隆过滤器
+// （1.44·f 位）节省约 15%–40% 的空间。
+//
+// 构建过程（剥离）：不断找只被一个 key 占用的位置，把这个 key 连同位置压栈并从其余位置移除；
+// 全部剥离后逆序赋值。剥离可能因为哈希成环而失败，此时换一组种子重试。
+//
+// 持久化格式（小端）：
+// "XORF" | 版本 u16 | 指纹位数 u8 | key 数 u64 | 段长 u64 | 种子 2×u64 | 指纹 3·段长×(f/8) | CRC-32
+
+use crate::bloom_filter::DoubleHasher;
+use crate::codec::{DecodeError, Decoder, Encoder};
+use rand::random;
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::io;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::ops::BitXor;
+
+const MAX_ATTEMPTS: usize = 100;
+const MAGIC: &[u8; 4] = b"XORF";
+const FORMAT_VERSION: u16 = 1;
+
+pub trait Fingerprint: Copy + Default + Eq + Debug + BitXor<Output = Self> {
+    const BITS: u32;
+
+    /// Keeps the low `BITS` bits.
+    fn from_u64(value: u64) -> Self;
+
+    fn to_u64(self) -> u64;
+}
+
+impl Fingerprint for u8 {
+    const BITS: u32 = u8::BITS;
+
+    fn from_u64(value: u64) -> Self {
+        value as u8
+    }
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+}
+
+impl Fingerprint for u16 {
+    const BITS: u32 = u16::BITS;
+
+    fn from_u64(value: u64) -> Self {
+        value as u16
+    }
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+}
+
+#[allow(unused)]
+pub type Xor8<T> = XorFilter<T, u8>;
+#[allow(unused)]
+pub type Xor16<T> = XorFilter<T, u16>;
+
+#[allow(unused)]
+pub struct XorFilter<T: ?Sized, F = u8> {
+    fingerprints: Vec<F>,
+    segment_len: usize,
+    hasher: DoubleHasher,
+    len: usize,
+    _phantom: PhantomData<T>,
+}
+
+#[allow(unused)]
+impl<T: ?Sized + Hash, F: Fingerprint> XorFilter<T, F> {
+    pub fn from_slice<K: Borrow<T>>(keys: &[K]) -> Result<Self, XorBuildError> {
+        Self::from_keys(keys.iter().map(|key| key.borrow()))
+    }
+
+    /// Builds the filter from `keys`; duplicates are ignored.
+    pub fn from_keys<'a, I>(keys: I) -> Result<Self, XorBuildError>
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        let keys: Vec<&T> = keys.into_iter().collect();
+        for _ in 0..MAX_ATTEMPTS {
+            if let Some(filter) = Self::try_build(&keys, [random(), random()]) {
+                return Ok(filter);
+            }
+        }
+        Err(XorBuildError {
+            attempts: MAX_ATTEMPTS,
+        })
+    }
+
+    fn try_build(keys: &[&T], seeds: [u64; 2]) -> Option<Self> {
+        let hasher = DoubleHasher::with_seeds(seeds);
+        let mut hashes: Vec<(u64, u64)> = keys.iter().map(|key| hasher.cal_hash(*key)).collect();
+        // 同一个 key 出现多次会让剥离永远失败，先按哈希去重
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let segment_len = Self::segment_len_for(hashes.len());
+        let mut filter = XorFilter {
+            fingerprints: vec![F::default(); segment_len * 3],
+            segment_len,
+            hasher,
+            len: hashes.len(),
+            _phantom: PhantomData,
+        };
+
+        // 每个位置记录占用它的 key 个数，以及这些 key 下标的异或
+        let mut counts = vec![0u32; segment_len * 3];
+        let mut key_xor = vec![0usize; segment_len * 3];
+        for (key, &hash_tuple) in hashes.iter().enumerate() {
+            for slot in filter.slots(hash_tuple) {
+                counts[slot] += 1;
+                key_xor[slot] ^= key;
+            }
+        }
+        let mut queue: Vec<usize> = (0..counts.len())
+            .filter(|&slot| counts[slot] == 1)
+            .collect();
+        let mut stack = Vec::with_capacity(hashes.len());
+        while let Some(slot) = queue.pop() {
+            if counts[slot] != 1 {
+                continue;
+            }
+            let key = key_xor[slot];
+            stack.push((key, slot));
+            for other in filter.slots(hashes[key]) {
+                counts[other] -= 1;
+                key_xor[other] ^= key;
+                if counts[other] == 1 {
+                    queue.push(other);
+                }
+            }
+        }
+        if stack.len() != hashes.len() {
+            return None;
+        }
+
+        for &(key, slot) in stack.iter().rev() {
+            let hash_tuple = hashes[key];
+            let others = filter
+                .slots(hash_tuple)
+                .into_iter()
+                .filter(|&other| other != slot)
+                .fold(F::default(), |acc, other| acc ^ filter.fingerprints[other]);
+            filter.fingerprints[slot] = Self::fingerprint(hash_tuple) ^ others;
+        }
+        Some(filter)
+    }
+
+    // 数组长度 1.23n + 32，平均分成三段
+    fn segment_len_for(len: usize) -> usize {
+        ((len as f64 * 1.23).ceil() as usize).saturating_add(32) / 3
+    }
+
+    /// 把 32 位哈希映射到 [0, n)，比取模快且分布相同
+    fn reduce(hash: u32, n: usize) -> usize {
+        ((hash as u64 * n as u64) >> 32) as usize
+    }
+
+    fn slots(&self, hash_tuple: (u64, u64)) -> [usize; 3] {
+        let hash = hash_tuple.0;
+        [
+            Self::reduce(hash as u32, self.segment_len),
+            Self::reduce(hash.rotate_left(21) as u32, self.segment_len) + self.segment_len,
+            Self::reduce(hash.rotate_left(42) as u32, self.segment_len) + 2 * self.segment_len,
+        ]
+    }
+
+    fn fingerprint(hash_tuple: (u64, u64)) -> F {
+        F::from_u64(hash_tuple.1)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let hash_tuple = self.hasher.cal_hash(value);
+        let [slot0, slot1, slot2] = self.slots(hash_tuple);
+        Self::fingerprint(hash_tuple)
+            == self.fingerprints[slot0] ^ self.fingerprints[slot1] ^ self.fingerprints[slot2]
+    }
+
+    /// Number of distinct keys the filter was built from.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.fingerprints.len() * size_of::<F>()
+    }
+
+    pub fn bits_per_key(&self) -> f64 {
+        (self.size_in_bytes() * 8) as f64 / self.len.max(1) as f64
+    }
+
+    /// 2^−f
+    pub fn false_positive_rate(&self) -> f64 {
+        1f64 / (1u64 << F::BITS) as f64
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = Encoder::new(writer, MAGIC, FORMAT_VERSION)?;
+        encoder.write_u8(F::BITS as u8)?;
+        encoder.write_u64(self.len as u64)?;
+        encoder.write_u64(self.segment_len as u64)?;
+        for seed in self.hasher.seeds() {
+            encoder.write_u64(seed)?;
+        }
+        let bytes = F::BITS as usize / 8;
+        for fingerprint in &self.fingerprints {
+            encoder.write_bytes(&fingerprint.to_u64().to_le_bytes()[..bytes])?;
+        }
+        encoder.finish()
+    }
+
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, DecodeError> {
+        let (mut decoder, version) = Decoder::new(reader, MAGIC)?;
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        if decoder.read_u8()? as u32 != F::BITS {
+            return Err(DecodeError::Corrupt("fingerprint width mismatch"));
+        }
+        let len = usize::try_from(decoder.read_u64()?)
+            .map_err(|_| DecodeError::Corrupt("key count overflows usize"))?;
+        let segment_len = usize::try_from(decoder.read_u64()?)
+            .map_err(|_| DecodeError::Corrupt("segment length overflows usize"))?;
+        let seeds = [decoder.read_u64()?, decoder.read_u64()?];
+        // 头部在校验和之前读出，先确认两个长度互相吻合
+        if segment_len != Self::segment_len_for(len) {
+            return Err(DecodeError::Corrupt(
+                "segment length does not match key count",
+            ));
+        }
+        let fingerprints_len = segment_len
+            .checked_mul(3)
+            .ok_or(DecodeError::Corrupt("fingerprint count overflows usize"))?;
+        // 逐个读取，长度字段损坏时只会读到 EOF，而不会一次分配过大的内存
+        let mut fingerprints = Vec::new();
+        for _ in 0..fingerprints_len {
+            let fingerprint = match F::BITS {
+                8 => decoder.read_u8()? as u64,
+                _ => decoder.read_u16()? as u64,
+            };
+            fingerprints.push(F::from_u64(fingerprint));
+        }
+        decoder.finish()?;
+        Ok(XorFilter {
+            fingerprints,
+            segment_len,
+            hasher: DoubleHasher::with_seeds(seeds),
+            len,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Peeling failed with every seed tried, which in practice means the hasher is
+/// not independent enough for the keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XorBuildError {
+    attempts: usize,
+}
+
+impl Display for XorBuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "could not build xor filter after {} attempts",
+            self.attempts
+        )
+    }
+}
+
+impl Error for XorBuildError {}
+
+#[cfg(test)]
+mod test {
+    use crate::bloom_filter::BloomFilter;
+    use crate::codec::DecodeError;
+    use crate::xor_filter::{Xor16, Xor8};
+    use rand::random;
+    use std::collections::HashSet;
+
+    fn random_keys(count: usize) -> Vec<u64> {
+        let mut keys = HashSet::with_capacity(count);
+        while keys.len() < count {
+            keys.insert(random::<u64>());
+        }
+        keys.into_iter().collect()
+    }
+
+    #[test]
+    fn test_contains_all_keys() {
+        let keys = random_keys(100_000);
+        let xor8 = Xor8::<u64>::from_slice(&keys).unwrap();
+        let xor16 = Xor16::<u64>::from_keys(keys.iter()).unwrap();
+        assert_eq!(xor8.len(), keys.len());
+        assert!(keys.iter().all(|key| xor8.contains(key)));
+        assert!(keys.iter().all(|key| xor16.contains(key)));
+        assert!(xor8.bits_per_key() < 10f64);
+        assert!(xor16.bits_per_key() < 20f64);
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let keys = random_keys(400_000);
+        let (inserted, probes) = keys.split_at(200_000);
+        let xor8 = Xor8::<u64>::from_slice(inserted).unwrap();
+        let xor16 = Xor16::<u64>::from_slice(inserted).unwrap();
+        let measure = |contains: &dyn Fn(&u64) -> bool| {
+            probes.iter().filter(|key| contains(key)).count() as f64 / probes.len() as f64
+        };
+        let fpr8 = measure(&|key| xor8.contains(key));
+        let fpr16 = measure(&|key| xor16.contains(key));
+        let expected8 = xor8.false_positive_rate();
+        let expected16 = xor16.false_positive_rate();
+        assert!(
+            (fpr8 - expected8).abs() < expected8 * 0.15,
+            "xor8 fpr {fpr8}, expected {expected8}"
+        );
+        assert!(
+            fpr16 < expected16 * 3f64,
+            "xor16 fpr {fpr16}, expected {expected16}"
+        );
+
+        // 同样的误差率，布隆过滤器要多用约 17% 的空间
+        let bloom = BloomFilter::<u64>::new(inserted.len(), xor8.false_positive_rate());
+        assert!(xor8.size_in_bytes() < bloom.size_in_bytes());
+    }
+
+    #[test]
+    fn test_small_and_duplicate_keys() {
+        let empty = Xor8::<str>::from_slice::<&str>(&[]).unwrap();
+        assert!(empty.is_empty());
+        for n in 1..64 {
+            let words: Vec<String> = (0..n).map(|i| format!("word-{}", i % 7)).collect();
+            let filter = Xor8::<str>::from_slice(&words).unwrap();
+            assert_eq!(filter.len(), n.min(7));
+            assert!(words.iter().all(|word| filter.contains(word)));
+        }
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let keys = random_keys(10_000);
+        let filter = Xor16::<u64>::from_slice(&keys).unwrap();
+        let mut buf = Vec::new();
+        filter.write_to(&mut buf).unwrap();
+        assert_eq!(buf.len(), 4 + 2 + 1 + 8 * 4 + filter.size_in_bytes() + 4);
+        let loaded = Xor16::<u64>::read_from(buf.as_slice()).unwrap();
+        assert_eq!(loaded.len(), filter.len());
+        assert!(keys.iter().all(|key| loaded.contains(key)));
+        assert_eq!(loaded.fingerprints, filter.fingerprints);
+
+        assert!(matches!(
+            Xor8::<u64>::read_from(buf.as_slice()),
+            Err(DecodeError::Corrupt(_))
+        ));
+        assert!(matches!(
+            BloomFilter::<u64>::read_from(buf.as_slice()),
+            Err(DecodeError::BadMagic(_))
+        ));
+        let mut flipped = buf.clone();
+        flipped[60] ^= 1;
+        assert!(matches!(
+            Xor16::<u64>::read_from(flipped.as_slice()),
+            Err(DecodeError::ChecksumMismatch { .. })
+        ));
+        let mut bad_version = buf.clone();
+        bad_version[4] = 9;
+        assert!(matches!(
+            Xor16::<u64>::read_from(bad_version.as_slice()),
+            Err(DecodeError::UnsupportedVersion(9))
+        ));
+    }
+
+    // 头部字段在校验和之前读出，损坏时要返回错误而不是溢出或按错误的长度读取
+    #[test]
+    fn test_read_rejects_corrupt_header() {
+        let filter = Xor8::<u64>::from_slice(&random_keys(1_000)).unwrap();
+        let mut buf = Vec::new();
+        filter.write_to(&mut buf).unwrap();
+        // magic 4 | 版本 2 | 指纹位数 1 | key 数 8 | 段长 8
+        let (len_at, segment_at) = (7, 15);
+        for (offset, value) in [
+            (segment_at, u64::MAX),
+            (segment_at, u64::MAX / 3 + 1),
+            (segment_at, 0),
+            (segment_at, filter.segment_len as u64 + 1),
+            (len_at, u64::MAX),
+            (len_at, 2_000),
+        ] {
+            let mut corrupt = buf.clone();
+            corrupt[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+            assert!(
+                matches!(
+                    Xor8::<u64>::read_from(corrupt.as_slice()),
+                    Err(DecodeError::Corrupt(_))
+                ),
+                "offset {offset}, value {value}"
+            );
+        }
+    }
+}